@@ -1,7 +1,17 @@
 use std::str::FromStr;
 use menv::Flag;
 menv::require_envs! {
-    (assert_env_vars, any_set, gen_help);
+    (
+        assert_env_vars, any_set, gen_help,
+        try_assert = try_assert_env_vars,
+        status = env_status,
+        help_table = gen_help_table,
+        help_markdown = gen_help_markdown,
+        help_roff = gen_help_roff,
+        env_example = gen_env_example,
+        vars = VARS,
+        json_schema = gen_json_schema,
+    );
 
     hm, "HM", Hm<String>,
     "HM should be set to something.";
@@ -17,8 +27,131 @@ menv::require_envs! {
 
     better_flag~, "BETTER_FLAG", Flag,
     "BETTER_FLAG is true when set, and false when not.";
+
+    toggle~, "TOGGLE", menv::Toggle,
+    "TOGGLE can be set to on or off, and is off when unset or empty.";
+
+    fallible!, "FALLIBLE", u32,
+    "FALLIBLE must be set, but reading it does not panic.";
+
+    port = 8080, "PORT", u16,
+    "PORT can be set, but defaults to 8080.";
+
+    negative = -1, "NEGATIVE", i32,
+    "NEGATIVE can be set, but defaults to -1.";
+
+    greeting = "hello", "GREETING", Hm<String>,
+    "GREETING can be set, but defaults to hello.";
+
+    computed = 2 + 2, "COMPUTED", u64,
+    "COMPUTED can be set, but defaults to 4.";
+
+    timeout = "1m30s", "TIMEOUT", menv::Duration,
+    "TIMEOUT can be set, but defaults to 1m30s.";
+
+    poll_interval~, "POLL_INTERVAL", menv::Duration<menv::duration::Millis>,
+    "POLL_INTERVAL can be set, in milliseconds if no unit is given.";
+
+    cache_size = "512MiB", "CACHE_SIZE", menv::ByteSize,
+    "CACHE_SIZE can be set, but defaults to 512MiB.";
+
+    body_limit = 65536, "BODY_LIMIT", menv::ByteSize,
+    "BODY_LIMIT can be set, but defaults to 64KiB.";
+
+    names*, "NAMES", String,
+    "NAMES can be set to a comma-separated list of names.";
+
+    ports!, "PORTS", menv::List<u16, ':'>,
+    "PORTS must be set to a colon-separated list of ports.";
+
+    labels~, "LABELS", menv::Map<String, Hm<String>>,
+    "LABELS can be set to labels like `team=infra,tier=gold`.";
+
+    weights?, "WEIGHTS", menv::Map<char, u8, ';', ':'>,
+    "WEIGHTS can be set to weights like `a:1;b:2`.";
+}
+
+mod cached {
+    use super::Hm;
+    use menv::Flag;
+    menv::require_envs! {
+        (assert_env_vars, any_set, gen_help);
+        #![cached]
+
+        hm, "CACHED_HM", Hm<String>,
+        "CACHED_HM should be set to something.";
+
+        huh?, "CACHED_HUH", String,
+        "CACHED_HUH can be set, but does not have to be.";
+
+        better_flag~, "CACHED_BETTER_FLAG", Flag,
+        "CACHED_BETTER_FLAG is true when set, and false when not.";
+
+        fallible!, "CACHED_FALLIBLE", u32,
+        "CACHED_FALLIBLE must be set, but reading it does not panic.";
+
+        port = 8080, "CACHED_PORT", u16,
+        "CACHED_PORT can be set, but defaults to 8080.";
+    }
 }
 
+mod snapshot {
+    menv::require_envs! {
+        (assert_env_vars, any_set, gen_help, try_assert = try_assert_env_vars, status = env_status, snapshot = env_snapshot);
+
+        #[cached]
+        cached, "SNAPSHOT_CACHED", String,
+        "SNAPSHOT_CACHED should be set to something.";
+
+        huh?, "SNAPSHOT_HUH", String,
+        "SNAPSHOT_HUH can be set, but does not have to be.";
+
+        #[cached]
+        #[secret]
+        password, "SNAPSHOT_PASSWORD", String,
+        "SNAPSHOT_PASSWORD should be set to the database password.";
+
+        token?, "SNAPSHOT_TOKEN", menv::Secret<String>,
+        "SNAPSHOT_TOKEN can be set to an API token.";
+
+        #[cached]
+        origins*, "SNAPSHOT_ORIGINS", String,
+        "SNAPSHOT_ORIGINS can be set to the origins allowed to make requests.";
+    }
+}
+
+menv::require_envs! {
+    (assert_some_cached, any_some_cached, gen_some_cached_help);
+
+    #[cached]
+    some_cached, "SOME_CACHED", String,
+    "SOME_CACHED should be set to something.";
+
+    not_cached, "NOT_CACHED", String,
+    "NOT_CACHED should be set to something.";
+}
+
+#[derive(menv::FromEnv)]
+pub struct Derived {
+    /// DERIVED_PORT can be set, but defaults to 8080.
+    #[menv(var = "DERIVED_PORT", default = 8080)]
+    pub port: u16,
+    #[menv(var = "DERIVED_NAME", optional, help = "DERIVED_NAME can be set, but does not have to be.")]
+    pub name: Option<Hm<String>>,
+    #[menv(var = "DERIVED_FLAG", help = "DERIVED_FLAG is true when set.", default)]
+    pub flag: Flag,
+    #[menv(var = "DERIVED_COUNT", help = "DERIVED_COUNT must be set.")]
+    pub count: u32,
+    #[menv(var = "DERIVED_PIN", help = "DERIVED_PIN must be set.", secret)]
+    pub pin: u16,
+    #[menv(var = "DERIVED_PORTS", help = "DERIVED_PORTS can be set to a list of ports.", list)]
+    pub ports: Vec<u16>,
+    #[menv(var = "DERIVED_OFFSET", help = "DERIVED_OFFSET can be set, but defaults to minus the default port.", default = -DEFAULT_OFFSET)]
+    pub offset: i32,
+}
+
+const DEFAULT_OFFSET: i32 = 8080;
+
 /// This type is just an excuse to be able to write a generic type in the above macro invocation.
 pub struct Hm<T> {
     _x: T,
@@ -0,0 +1,350 @@
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use crate::{compile_error, flatten_groups, lit_parse};
+
+/// A single `require_envs!` declaration, like
+/// `#[cached] #[secret] pub(crate) server_port?, "FERRISCRAFT_USERS_PORT", u16, "help text"`.
+pub struct VarDecl {
+    /// Empty if no visibility was written.
+    pub vis: Vec<TokenTree>,
+    pub getter_name: Ident,
+    pub getter_mark: Mark,
+    pub var_name: Literal,
+    /// The contents of `var_name`, with escapes processed.
+    pub var_name_value: String,
+    pub getter_type: Vec<TokenTree>,
+    pub help_message: Literal,
+    /// Set by `#[cached]`, or by `#![cached]` on the whole invocation.
+    pub cached: bool,
+    /// Set by `#[secret]`, or by declaring a type which contains `Secret<T>`.
+    pub secret: bool,
+}
+
+/// Whether a type mentions `Secret` anywhere, like `menv::Secret<String>`, `Option<Secret<String>>`
+/// or `Map<String, Secret<String>>`, so that a value holding a secret is never printed.
+pub fn is_secret_type(ty: &[TokenTree]) -> bool {
+    ty.iter().any(|tree| match tree {
+        TokenTree::Ident(ident) => ident.to_string() == "Secret",
+        TokenTree::Group(group) => is_secret_type(&group.stream().into_iter().collect::<Vec<_>>()),
+        _ => false,
+    })
+}
+
+/// A default value, behind a marker telling the `require_envs!` internals whether it's a `literal`,
+/// which is parsed with `FromStr` like a value of the var would be, or any other `expr`.
+///
+/// `macro_rules!` can't make that decision itself, since once a `literal` fragment has taken
+/// the `-` of an expression like `-OFFSET`, it can't back out to try matching an `expr` instead.
+pub fn marked_default(default: &[TokenTree]) -> Vec<TokenTree> {
+    let literal = match &flatten_groups(default.to_vec())[..] {
+        [TokenTree::Literal(_)] => true,
+        [TokenTree::Punct(minus), TokenTree::Literal(_)] => minus.as_char() == '-',
+        [TokenTree::Ident(ident)] => matches!(&*ident.to_string(), "true" | "false"),
+        _ => false,
+    };
+    let span = default[0].span();
+    let marker = Ident::new(if literal { "literal" } else { "expr" }, span);
+    std::iter::once(TokenTree::Ident(marker))
+        .chain(default.iter().cloned())
+        .collect()
+}
+
+/// What follows the getter name, determining the getter's behavior when a var is unset.
+pub enum Mark {
+    Required,
+    /// `?`
+    Optional(Punct),
+    /// `~`
+    Default(Punct),
+    /// `!`
+    Fallible(Punct),
+    /// `*`
+    List(Punct),
+    /// `= default`
+    Value(Punct, Vec<TokenTree>),
+}
+
+/// A simple token parser, shared with the `FromEnv` derive.
+pub struct Cursor {
+    iter: Peekable<IntoIter<TokenTree>>,
+    // Used to point at the end of a declaration when something is missing from it.
+    last_span: Span,
+}
+impl Cursor {
+    pub fn new(tokens: Vec<TokenTree>) -> Self {
+        let last_span = tokens
+            .first()
+            .map(|tree| tree.span())
+            .unwrap_or_else(Span::call_site);
+        Self {
+            iter: tokens.into_iter().peekable(),
+            last_span,
+        }
+    }
+    pub fn next(&mut self) -> Option<TokenTree> {
+        let tree = self.iter.next()?;
+        self.last_span = tree.span();
+        Some(tree)
+    }
+    pub fn peek(&mut self) -> Option<&TokenTree> {
+        self.iter.peek()
+    }
+    pub fn peek_punct(&mut self, c: char) -> bool {
+        matches!(self.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == c)
+    }
+    /// Span of the next token, or of the last one if there are none left.
+    pub fn span(&mut self) -> Span {
+        match self.peek() {
+            Some(tree) => tree.span(),
+            None => self.last_span,
+        }
+    }
+    pub fn expect_comma(&mut self, after: &str) -> Result<(), TokenStream> {
+        if self.peek_punct(',') {
+            self.next();
+            Ok(())
+        } else {
+            Err(compile_error(
+                &format!("expected `,` after {after}"),
+                self.span(),
+            ))
+        }
+    }
+    /// Take tokens up until the next `,` which isn't nested inside some generic arguments.
+    ///
+    /// In types, every `<` opens generic arguments. In expressions,
+    /// only those in turbofishes (`::<`) do, since the rest are comparisons.
+    pub fn until_comma(&mut self, in_type: bool) -> Vec<TokenTree> {
+        let mut out = Vec::new();
+        let mut depth = 0usize;
+        let mut prev: Option<Punct> = None;
+        while let Some(tree) = self.peek() {
+            if let TokenTree::Punct(punct) = tree {
+                let after_colon = prev.as_ref().is_some_and(|prev| prev.as_char() == ':');
+                let after_joint_minus = prev
+                    .as_ref()
+                    .is_some_and(|prev| prev.as_char() == '-' && prev.spacing() == Spacing::Joint);
+                match punct.as_char() {
+                    ',' if depth == 0 => break,
+                    '<' if in_type || after_colon => depth += 1,
+                    // The `>` in `->` doesn't close anything.
+                    '>' if !after_joint_minus => depth = depth.saturating_sub(1),
+                    _ => {}
+                }
+                prev = Some(punct.clone());
+            } else {
+                prev = None;
+            }
+            out.push(self.next().unwrap());
+        }
+        out
+    }
+    /// Take a string literal, returning it along with its contents.
+    pub fn string_literal(&mut self, what: &str) -> Result<(Literal, String), TokenStream> {
+        let error = |span| compile_error(&format!("expected {what} as a string literal"), span);
+        let span = self.span();
+        let Some(TokenTree::Literal(lit)) = self.peek().cloned() else {
+            return Err(error(span));
+        };
+        let parsed =
+            lit_parse::Literal::parse(&lit).map_err(|e| e.into_iter().collect::<TokenStream>())?;
+        if !parsed.errors.is_empty() {
+            return Err(parsed.errors.into_iter().collect());
+        }
+        let lit_parse::LiteralData::String(value) = parsed.data() else {
+            return Err(error(span));
+        };
+        let value = value.clone();
+        self.next();
+        Ok((lit, value))
+    }
+}
+
+impl VarDecl {
+    /// Parse the tokens of a single declaration, without its terminating `;`.
+    pub fn parse(tokens: Vec<TokenTree>) -> Result<Self, TokenStream> {
+        let mut cursor = Cursor::new(tokens);
+
+        let mut cached = false;
+        let mut secret = false;
+        while cursor.peek_punct('#') {
+            let pound = cursor.next().unwrap();
+            let Some(TokenTree::Group(attr)) = cursor.next() else {
+                return Err(compile_error(
+                    "expected an attribute, like `#[cached]`",
+                    pound.span(),
+                ));
+            };
+            let mut attr = attr.stream().into_iter();
+            match (attr.next(), attr.next()) {
+                (Some(TokenTree::Ident(name)), None) if name.to_string() == "cached" => {
+                    cached = true
+                }
+                (Some(TokenTree::Ident(name)), None) if name.to_string() == "secret" => {
+                    secret = true
+                }
+                (Some(name), _) => {
+                    return Err(compile_error(
+                        &format!("unknown attribute `{name}`, expected `cached` or `secret`"),
+                        name.span(),
+                    ))
+                }
+                (None, _) => return Err(compile_error("expected an attribute name", pound.span())),
+            }
+        }
+
+        let mut vis = Vec::new();
+        if let Some(TokenTree::Ident(ident)) = cursor.peek() {
+            if ident.to_string() == "pub" {
+                vis.push(cursor.next().unwrap());
+                if let Some(TokenTree::Group(group)) = cursor.peek() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        vis.push(cursor.next().unwrap());
+                    }
+                }
+            }
+        }
+
+        let getter_name = match cursor.peek() {
+            Some(TokenTree::Ident(_)) => {
+                let Some(TokenTree::Ident(ident)) = cursor.next() else {
+                    unreachable!()
+                };
+                ident
+            }
+            _ => {
+                return Err(compile_error(
+                    "expected getter function name",
+                    cursor.span(),
+                ))
+            }
+        };
+
+        let getter_mark = match cursor.peek() {
+            Some(TokenTree::Punct(punct)) => match punct.as_char() {
+                ',' => Mark::Required,
+                '?' => Mark::Optional(punct.clone()),
+                '~' => Mark::Default(punct.clone()),
+                '!' => Mark::Fallible(punct.clone()),
+                '*' => Mark::List(punct.clone()),
+                '=' => Mark::Value(punct.clone(), Vec::new()),
+                c => {
+                    return Err(compile_error(
+                        &format!("unknown getter modifier `{c}`, expected one of `?`, `~`, `!`, `*` or `= default`"),
+                        punct.span(),
+                    ))
+                }
+            },
+            _ => return Err(compile_error("expected `,` after getter name", cursor.span())),
+        };
+        let getter_mark = match getter_mark {
+            Mark::Required => Mark::Required,
+            Mark::Value(eq, _) => {
+                cursor.next();
+                let default = cursor.until_comma(false);
+                if default.is_empty() {
+                    return Err(compile_error(
+                        "expected a default value after `=`",
+                        cursor.span(),
+                    ));
+                }
+                Mark::Value(eq, default)
+            }
+            mark => {
+                cursor.next();
+                mark
+            }
+        };
+        cursor.expect_comma(match getter_mark {
+            Mark::Required => "getter name",
+            Mark::Value(..) => "default value",
+            _ => "getter modifier",
+        })?;
+
+        let (var_name, var_name_value) = cursor.string_literal("env var name")?;
+        cursor.expect_comma("env var name")?;
+
+        let getter_type = cursor.until_comma(true);
+        let secret = secret || is_secret_type(&getter_type);
+        if getter_type.is_empty() {
+            return Err(compile_error(
+                "expected the type of the env var",
+                cursor.span(),
+            ));
+        }
+        if cursor.peek().is_none() {
+            return Err(compile_error(
+                "missing help text, expected `,` followed by a string literal",
+                cursor.span(),
+            ));
+        }
+        cursor.expect_comma("type")?;
+
+        let (help_message, _) = cursor.string_literal("help text")?;
+        if cursor.peek().is_some() {
+            return Err(compile_error("expected `;` after help text", cursor.span()));
+        }
+
+        Ok(Self {
+            vis,
+            getter_name,
+            getter_mark,
+            var_name,
+            var_name_value,
+            getter_type,
+            help_message,
+            cached,
+            secret,
+        })
+    }
+
+    /// Write this declaration back out in the form the `require_envs!` internals expect.
+    pub fn to_tokens(&self) -> Vec<TokenTree> {
+        let comma = || TokenTree::Punct(Punct::new(',', Spacing::Alone));
+        let mut out = self.secret_attr();
+        // We set a default visibility which is different from Rust's default to private.
+        if self.vis.is_empty() {
+            out.push(TokenTree::Ident(Ident::new("pub", self.getter_name.span())));
+        } else {
+            out.extend(self.vis.iter().cloned());
+        }
+        out.push(TokenTree::Ident(self.getter_name.clone()));
+        match &self.getter_mark {
+            Mark::Required => {}
+            Mark::Optional(punct)
+            | Mark::Default(punct)
+            | Mark::Fallible(punct)
+            | Mark::List(punct) => out.push(TokenTree::Punct(punct.clone())),
+            Mark::Value(eq, default) => {
+                out.push(TokenTree::Punct(eq.clone()));
+                out.extend(marked_default(default));
+            }
+        }
+        out.push(comma());
+        out.push(TokenTree::Literal(self.var_name.clone()));
+        out.push(comma());
+        out.extend(self.getter_type.iter().cloned());
+        out.push(comma());
+        out.push(TokenTree::Literal(self.help_message.clone()));
+        out
+    }
+
+    /// `#[secret]` if this var is secret, which the `require_envs!` internals expect in front of
+    /// the declaration, or in front of the env var name when it's passed on to `@lookup` or `@require`.
+    pub fn secret_attr(&self) -> Vec<TokenTree> {
+        if !self.secret {
+            return Vec::new();
+        }
+        let span = self.var_name.span();
+        vec![
+            TokenTree::Punct(Punct::new('#', Spacing::Alone)),
+            TokenTree::Group(Group::new(
+                Delimiter::Bracket,
+                TokenTree::Ident(Ident::new("secret", span)).into(),
+            )),
+        ]
+    }
+}
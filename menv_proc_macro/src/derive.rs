@@ -0,0 +1,455 @@
+use std::str::FromStr;
+
+use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
+
+use crate::decl::{is_secret_type, marked_default, Cursor, Mark, VarDecl};
+use crate::options::Options;
+use crate::{call_require_envs, compile_error, lint};
+
+struct Field {
+    attrs: Vec<Group>,
+    name: Ident,
+    ty: Vec<TokenTree>,
+}
+
+pub fn from_env(input: TokenStream) -> TokenStream {
+    let (name, fields) = match parse_struct(input) {
+        Ok(x) => x,
+        Err(e) => return e,
+    };
+    let mut decls = Vec::new();
+    let mut errors = Vec::new();
+    for field in fields {
+        match field_decl(field) {
+            Ok(decl) => decls.push(decl),
+            Err(e) => errors.push(e),
+        }
+    }
+    let mut warnings = Vec::new();
+    lint::lint(
+        &mut decls,
+        &Options::default(),
+        None,
+        &mut errors,
+        &mut warnings,
+    );
+    if !errors.is_empty() {
+        return errors.into_iter().chain(warnings).collect();
+    }
+    let mut output = generate(name, decls);
+    output.extend(warnings);
+    output
+}
+
+fn skip_attrs(cursor: &mut Cursor) -> Vec<Group> {
+    let mut attrs = Vec::new();
+    while cursor.peek_punct('#') {
+        cursor.next();
+        if let Some(TokenTree::Group(attr)) = cursor.next() {
+            attrs.push(attr);
+        }
+    }
+    attrs
+}
+
+fn skip_vis(cursor: &mut Cursor) {
+    if matches!(cursor.peek(), Some(TokenTree::Ident(ident)) if ident.to_string() == "pub") {
+        cursor.next();
+        if matches!(cursor.peek(), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Parenthesis)
+        {
+            cursor.next();
+        }
+    }
+}
+
+fn parse_struct(input: TokenStream) -> Result<(Ident, Vec<Field>), TokenStream> {
+    const ONLY_STRUCTS: &str = "`FromEnv` can only be derived for structs with named fields";
+    let mut cursor = Cursor::new(input.into_iter().collect());
+    skip_attrs(&mut cursor);
+    skip_vis(&mut cursor);
+    match cursor.next() {
+        Some(TokenTree::Ident(kw)) if kw.to_string() == "struct" => {}
+        Some(tree) => return Err(compile_error(ONLY_STRUCTS, tree.span())),
+        None => return Err(compile_error(ONLY_STRUCTS, Span::call_site())),
+    }
+    let Some(TokenTree::Ident(name)) = cursor.next() else {
+        return Err(compile_error(ONLY_STRUCTS, cursor.span()));
+    };
+    let body = match cursor.next() {
+        Some(TokenTree::Group(body)) if body.delimiter() == Delimiter::Brace => body,
+        Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => {
+            return Err(compile_error(
+                "`FromEnv` can't be derived for generic structs",
+                punct.span(),
+            ))
+        }
+        _ => return Err(compile_error(ONLY_STRUCTS, name.span())),
+    };
+
+    let mut fields = Vec::new();
+    let mut cursor = Cursor::new(body.stream().into_iter().collect());
+    while cursor.peek().is_some() {
+        let attrs = skip_attrs(&mut cursor);
+        skip_vis(&mut cursor);
+        let Some(TokenTree::Ident(name)) = cursor.next() else {
+            return Err(compile_error("expected a field name", cursor.span()));
+        };
+        if !cursor.peek_punct(':') {
+            return Err(compile_error(
+                "expected `:` after field name",
+                cursor.span(),
+            ));
+        }
+        cursor.next();
+        let ty = cursor.until_comma(true);
+        if cursor.peek_punct(',') {
+            cursor.next();
+        }
+        fields.push(Field { attrs, name, ty });
+    }
+    Ok((name, fields))
+}
+
+/// Turn a field into the `require_envs!` declaration it's equivalent to.
+fn field_decl(field: Field) -> Result<VarDecl, TokenStream> {
+    let mut var = None;
+    let mut help = None;
+    let mut mark = Mark::Required;
+    let mut secret = false;
+    let mut docs = Vec::new();
+    for attr in field.attrs {
+        let mut cursor = Cursor::new(attr.stream().into_iter().collect());
+        let Some(TokenTree::Ident(kind)) = cursor.next() else {
+            continue;
+        };
+        match kind.to_string().as_str() {
+            "doc" => {
+                cursor.next();
+                if let Ok((_, doc)) = cursor.string_literal("doc comment") {
+                    docs.push(doc);
+                }
+            }
+            "menv" => {
+                let Some(TokenTree::Group(args)) = cursor.next() else {
+                    return Err(compile_error(
+                        "expected arguments, like `#[menv(var = \"...\")]`",
+                        kind.span(),
+                    ));
+                };
+                parse_menv_attr(args, &mut var, &mut help, &mut mark, &mut secret)?;
+            }
+            _ => {}
+        }
+    }
+
+    let Some((var_name, var_name_value)) = var else {
+        return Err(compile_error(
+            "missing env var name, expected `#[menv(var = \"...\")]`",
+            field.name.span(),
+        ));
+    };
+    // Doc comments make for a natural source of help text, so we fall back to them.
+    let help_message = match help {
+        Some(help) => help,
+        None if !docs.is_empty() => {
+            let lines: Vec<&str> = docs
+                .iter()
+                .map(|line| line.strip_prefix(' ').unwrap_or(line))
+                .collect();
+            let mut help = Literal::string(&lines.join("\n"));
+            help.set_span(field.name.span());
+            help
+        }
+        None => {
+            return Err(compile_error(
+                "missing help text, expected a doc comment or `#[menv(help = \"...\")]`",
+                field.name.span(),
+            ))
+        }
+    };
+    let getter_type = match mark {
+        Mark::Optional(_) => generic_inner(&field.ty, "Option").ok_or_else(|| {
+            compile_error(
+                "fields marked `optional` must have a type of the form `Option<T>`",
+                field.ty[0].span(),
+            )
+        })?,
+        Mark::List(_) => generic_inner(&field.ty, "Vec").ok_or_else(|| {
+            compile_error(
+                "fields marked `list` must have a type of the form `Vec<T>`",
+                field.ty[0].span(),
+            )
+        })?,
+        _ => field.ty,
+    };
+    Ok(VarDecl {
+        vis: Vec::new(),
+        getter_name: field.name,
+        getter_mark: mark,
+        var_name,
+        var_name_value,
+        secret: secret || is_secret_type(&getter_type),
+        getter_type,
+        help_message,
+        cached: false,
+    })
+}
+
+fn parse_menv_attr(
+    args: Group,
+    var: &mut Option<(Literal, String)>,
+    help: &mut Option<Literal>,
+    mark: &mut Mark,
+    secret: &mut bool,
+) -> Result<(), TokenStream> {
+    let mut cursor = Cursor::new(args.stream().into_iter().collect());
+    while let Some(tree) = cursor.next() {
+        let TokenTree::Ident(key) = tree else {
+            return Err(compile_error(
+                "expected `var`, `help`, `optional`, `default`, `list` or `secret`",
+                tree.span(),
+            ));
+        };
+        let mut set_mark = |new: Mark| {
+            if !matches!(mark, Mark::Required) {
+                return Err(compile_error(
+                    "`optional`, `default` and `list` can only be given once, and not together",
+                    key.span(),
+                ));
+            }
+            *mark = new;
+            Ok(())
+        };
+        match key.to_string().as_str() {
+            "var" => {
+                expect_eq(&mut cursor, &key)?;
+                *var = Some(cursor.string_literal("env var name")?);
+            }
+            "help" => {
+                expect_eq(&mut cursor, &key)?;
+                *help = Some(cursor.string_literal("help text")?.0);
+            }
+            "optional" => set_mark(Mark::Optional(spanned_punct('?', key.span())))?,
+            "default" if cursor.peek_punct('=') => {
+                let Some(TokenTree::Punct(eq)) = cursor.next() else {
+                    unreachable!()
+                };
+                let default = cursor.until_comma(false);
+                if default.is_empty() {
+                    return Err(compile_error(
+                        "expected a default value after `=`",
+                        cursor.span(),
+                    ));
+                }
+                set_mark(Mark::Value(eq, default))?
+            }
+            "default" => set_mark(Mark::Default(spanned_punct('~', key.span())))?,
+            "list" => set_mark(Mark::List(spanned_punct('*', key.span())))?,
+            "secret" => *secret = true,
+            _ => {
+                return Err(compile_error(
+                    &format!(
+                        "unknown argument `{key}`, expected `var`, `help`, `optional`, `default`, `list` or `secret`"
+                    ),
+                    key.span(),
+                ))
+            }
+        }
+        if cursor.peek().is_some() {
+            cursor.expect_comma("argument")?;
+        }
+    }
+    Ok(())
+}
+
+fn expect_eq(cursor: &mut Cursor, key: &Ident) -> Result<(), TokenStream> {
+    if cursor.peek_punct('=') {
+        cursor.next();
+        Ok(())
+    } else {
+        Err(compile_error(
+            &format!("expected `=` after `{key}`"),
+            cursor.span(),
+        ))
+    }
+}
+
+fn spanned_punct(c: char, span: Span) -> Punct {
+    let mut punct = Punct::new(c, Spacing::Alone);
+    punct.set_span(span);
+    punct
+}
+
+/// Get `T` out of `Option<T>` or similar, however the path to `outer` is written.
+fn generic_inner(ty: &[TokenTree], outer: &str) -> Option<Vec<TokenTree>> {
+    let open = ty
+        .iter()
+        .position(|tree| matches!(tree, TokenTree::Punct(punct) if punct.as_char() == '<'))?;
+    let is_outer =
+        matches!(&ty[..open], [.., TokenTree::Ident(ident)] if ident.to_string() == outer);
+    let is_closed = matches!(ty.last(), Some(TokenTree::Punct(punct)) if punct.as_char() == '>');
+    if !is_outer || !is_closed || open + 2 > ty.len() - 1 {
+        return None;
+    }
+    Some(ty[open + 1..ty.len() - 1].to_vec())
+}
+
+fn tokens(s: &str) -> TokenStream {
+    TokenStream::from_str(s).unwrap()
+}
+
+fn generate(name: Ident, decls: Vec<VarDecl>) -> TokenStream {
+    let krate = tokens("::menv");
+    // These are only visible to our own code, so fields can be named anything at all.
+    let failures = TokenTree::Ident(Ident::new("failures", Span::mixed_site()));
+    let x = TokenTree::Ident(Ident::new("x", Span::mixed_site()));
+    let source = TokenTree::Ident(Ident::new("source", Span::mixed_site()));
+
+    let mut from_env = Vec::<TokenStream>::new();
+    from_env.push(tokens("let mut"));
+    from_env.push(failures.clone().into());
+    from_env.push(tokens("= ::std::vec::Vec::new();"));
+    let mut fields = Vec::<TokenStream>::new();
+    for decl in &decls {
+        let mut args = vec![
+            TokenTree::Literal(decl.var_name.clone()),
+            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+        ];
+        args.extend(decl.getter_type.iter().cloned());
+        args.push(TokenTree::Punct(Punct::new(',', Spacing::Alone)));
+        let mut lookup_args = vec![
+            source.clone(),
+            TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+        ];
+        lookup_args.extend(decl.secret_attr());
+        lookup_args.extend(args.iter().cloned());
+        lookup_args.push(TokenTree::Literal(decl.help_message.clone()));
+
+        let value = match &decl.getter_mark {
+            Mark::Required | Mark::Fallible(_) => {
+                call_require_envs(krate.clone(), "require", lookup_args)
+            }
+            Mark::Optional(_) => call_require_envs(krate.clone(), "lookup", lookup_args),
+            Mark::List(_) => call_require_envs(krate.clone(), "list", lookup_args),
+            Mark::Default(_) => {
+                let mut value = call_require_envs(krate.clone(), "lookup", lookup_args);
+                value.extend(tokens(".map(::core::option::Option::unwrap_or_default)"));
+                value
+            }
+            Mark::Value(_, default) => {
+                let mut default_args = args;
+                default_args.extend(marked_default(default));
+                let default = call_require_envs(krate.clone(), "default", default_args);
+                let mut value = call_require_envs(krate.clone(), "lookup", lookup_args);
+                value.extend(tokens(".map"));
+                value.extend(group(
+                    Delimiter::Parenthesis,
+                    [
+                        tokens("|"),
+                        x.clone().into(),
+                        tokens("|"),
+                        x.clone().into(),
+                        tokens(".unwrap_or_else"),
+                        group(Delimiter::Parenthesis, [tokens("||"), default]),
+                    ],
+                ));
+                value
+            }
+        };
+        from_env.push(tokens("let"));
+        from_env.push(TokenTree::Ident(decl.getter_name.clone()).into());
+        from_env.push(tokens("= ::menv::__private::collect"));
+        from_env.push(group(
+            Delimiter::Parenthesis,
+            [
+                tokens("&mut"),
+                failures.clone().into(),
+                tokens(","),
+                TokenTree::Literal(Literal::string(&decl.getter_name.to_string())).into(),
+                tokens(","),
+                value,
+            ],
+        ));
+        from_env.push(tokens(";"));
+
+        fields.push(TokenTree::Ident(decl.getter_name.clone()).into());
+        fields.push(tokens(":"));
+        fields.push(TokenTree::Ident(decl.getter_name.clone()).into());
+        fields.push(tokens(".unwrap(),"));
+    }
+    from_env.push(tokens("if !"));
+    from_env.push(failures.clone().into());
+    from_env.push(tokens(".is_empty()"));
+    from_env.push(group(
+        Delimiter::Brace,
+        [
+            tokens("return ::core::result::Result::Err"),
+            group(
+                Delimiter::Parenthesis,
+                [failures.into(), tokens(".into_iter().collect()")],
+            ),
+            tokens(";"),
+        ],
+    ));
+    from_env.push(tokens("::core::result::Result::Ok"));
+    from_env.push(group(
+        Delimiter::Parenthesis,
+        [tokens("Self"), group(Delimiter::Brace, fields)],
+    ));
+
+    let help: Vec<TokenStream> = decls
+        .into_iter()
+        .flat_map(|decl| {
+            [
+                call_require_envs(krate.clone(), "etext", decl.to_tokens()),
+                tokens(","),
+            ]
+        })
+        .collect();
+
+    let body = [
+        tokens(
+            "/// Read each field from its env var,
+            /// collecting every failure instead of stopping at the first.
+            pub fn from_env() -> ::core::result::Result<Self, ::menv::Errors> {
+                Self::from_source(&::menv::ProcessEnv)
+            }
+            /// Like [`Self::from_env`], but reads the env vars from `source`.
+            pub fn from_source",
+        ),
+        group(
+            Delimiter::Parenthesis,
+            [
+                source.into(),
+                tokens(": &(impl ::menv::Source + ?::core::marker::Sized)"),
+            ],
+        ),
+        tokens("-> ::core::result::Result<Self, ::menv::Errors>"),
+        group(Delimiter::Brace, from_env),
+        tokens(
+            "/// Returns the collected help messages for the env vars read by [`Self::from_env`].
+            pub fn help() -> ::std::string::String",
+        ),
+        group(
+            Delimiter::Brace,
+            [
+                tokens("::menv::__private::join_help"),
+                group(
+                    Delimiter::Parenthesis,
+                    [tokens("&"), group(Delimiter::Bracket, help)],
+                ),
+            ],
+        ),
+    ];
+    [
+        tokens("impl"),
+        TokenTree::Ident(name).into(),
+        group(Delimiter::Brace, body),
+    ]
+    .into_iter()
+    .collect()
+}
+
+fn group(delimiter: Delimiter, stream: impl IntoIterator<Item = TokenStream>) -> TokenStream {
+    TokenTree::Group(Group::new(delimiter, stream.into_iter().collect())).into()
+}
@@ -1,100 +1,199 @@
-// assert_var_body, any_set_body, help_body, getters
+// assert_var_body, any_set_body, help_body, check_body, getters, prime_body, extras, info_body,
+// status_body, var_names, default_text, FromEnv
 
 use proc_macro::{Delimiter, Group, Ident, Literal, Punct, Spacing, Span, TokenStream, TokenTree};
 use std::str::FromStr;
 
+mod decl;
+mod derive;
+mod lint;
 mod lit_parse;
+mod options;
 
-// struct VarDecl {
-//     getter_name: Ident,
-//     getter_mark: Option<Punct>,
-//     var_name: Literal,
-//     getter_type: Vec<TokenTree>,
-//     help_message: Literal,
-// }
-
-// We *could* parse into the above, but we don't actually have to,
-// since all we really need is to divide the input stream into pieces
-// our existing require_envs! internals can handle.
-struct VagueVarDecl {
-    tokens: Vec<TokenTree>,
-}
+use decl::{Mark, VarDecl};
+use options::Options;
 
 struct Stream {
-    krate: Ident,
-    decls: Vec<VagueVarDecl>,
+    krate: TokenStream,
+    decls: Vec<VarDecl>,
     // All errors from parsing should be shoved into this field,
     // and the parser should limp along to the end no matter what.
     errors: Vec<TokenStream>,
+    warnings: Vec<TokenStream>,
 }
 impl Stream {
     fn parse(input: TokenStream) -> Self {
         // dbg!(&input);
-        let mut input = input.into_iter();
+        let mut input = input.into_iter().peekable();
         // We can assume the $crate token is always present,
         // because require_envs! always puts it in front.
         let krate = input.next().unwrap();
-        let TokenTree::Ident(krate) = krate else { panic!() };
+        let TokenTree::Ident(_) = krate else { panic!() };
+        let krate = TokenStream::from(krate);
+        // Followed by the names of the functions the invocation generates, besides the getters.
+        let Some(TokenTree::Group(functions)) = input.next() else {
+            panic!()
+        };
+        let functions = generated_functions(functions);
         let mut decls = Vec::new();
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let options = Options::parse(&mut input, &mut errors);
 
         let mut cdecl = Vec::new();
+        let mut finish = |cdecl: Vec<TokenTree>| {
+            // Stray semicolons are harmless, so we let them slide.
+            if cdecl.is_empty() {
+                return;
+            }
+            // Declarations which fail to parse are left out,
+            // so the only errors reported for them are our own.
+            match VarDecl::parse(cdecl) {
+                Ok(decl) => decls.push(decl),
+                Err(e) => errors.push(e),
+            }
+        };
         for tree in input {
             if let TokenTree::Punct(punct) = &tree {
                 if punct.as_char() == ';' {
-                    decls.push(VagueVarDecl { tokens: cdecl });
-                    cdecl = Vec::new();
+                    finish(std::mem::take(&mut cdecl));
                     continue;
                 }
             }
             cdecl.push(tree);
         }
-        if cdecl.len() != 0 {
-            decls.push(VagueVarDecl { tokens: cdecl });
+        finish(cdecl);
+        if options.cached {
+            decls.iter_mut().for_each(|decl| decl.cached = true);
         }
+        lint::lint(
+            &mut decls,
+            &options,
+            Some(&functions),
+            &mut errors,
+            &mut warnings,
+        );
 
         Self {
             krate,
             decls,
-            errors: Vec::new(),
+            errors,
+            warnings,
         }
     }
 }
 
-fn call_require_envs(krate: Ident, method: &str, input: Vec<TokenTree>) -> TokenStream {
-    let krate_span = krate.span();
+/// Parse the input of a proc macro which takes an extra token tree between `$crate` and the declarations,
+/// like the name of the `source` parameter of the `_from` function it generates the body of.
+fn parse_with_arg(input: TokenStream) -> (TokenTree, Stream) {
+    let mut input = input.into_iter();
+    let krate = input.next().unwrap();
+    let arg = input.next().unwrap();
+    (
+        arg,
+        Stream::parse([krate].into_iter().chain(input).collect()),
+    )
+}
+
+/// Where the functions which don't take a source read env vars from, given the user's `key = name` pairs.
+///
+/// This is the snapshot if one was requested with `snapshot = name`, and the process environment otherwise.
+fn default_source(krate: &TokenStream, pairs: &Group) -> TokenTree {
+    let source = match pairs_of(pairs.clone()).find(|(key, _)| key.to_string() == "snapshot") {
+        Some((_, name)) => [
+            TokenTree::Ident(name),
+            TokenTree::Group(Group::new(Delimiter::Parenthesis, TokenStream::new())),
+        ]
+        .into_iter()
+        .collect(),
+        None => {
+            let mut source = TokenStream::from_str("&").unwrap();
+            source.extend(krate.clone());
+            source.extend(TokenStream::from_str("::ProcessEnv").unwrap());
+            source
+        }
+    };
+    TokenTree::Group(Group::new(Delimiter::Bracket, source))
+}
+
+/// Put the name of a `source` parameter in front of the tokens of a declaration.
+fn with_source(source: &TokenTree, decl: &VarDecl) -> Vec<TokenTree> {
+    let mut tokens = vec![
+        source.clone(),
+        TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+    ];
+    tokens.extend(decl.to_tokens());
+    tokens
+}
+
+/// The names of the functions given as `kind = name` pairs, including their `_from` variants.
+fn generated_functions(pairs: Group) -> Vec<Ident> {
+    pairs_of(pairs)
+        .flat_map(|(kind, name)| {
+            let from = has_from_variant(&kind.to_string()).then(|| from_name(&name));
+            std::iter::once(name).chain(from)
+        })
+        .collect()
+}
+
+/// Whether the kind of function generated by `kind = name` reads env vars, and so gets a `_from` variant.
+/// Those which only describe the declarations have no need for one, and neither does the snapshot function.
+fn has_from_variant(kind: &str) -> bool {
+    !matches!(
+        kind,
+        "help"
+            | "snapshot"
+            | "vars"
+            | "help_table"
+            | "help_markdown"
+            | "help_roff"
+            | "json_schema"
+            | "env_example"
+    )
+}
+
+/// The name of the `_from` variant of a generated function.
+fn from_name(name: &Ident) -> Ident {
+    let text = name.to_string();
+    let text = text.strip_prefix("r#").unwrap_or(&text);
+    Ident::new(&format!("{text}_from"), name.span())
+}
+
+/// `krate` is the path to the `menv` crate, which is `$crate` when we're called by `require_envs!`.
+fn call_require_envs(krate: TokenStream, method: &str, input: Vec<TokenTree>) -> TokenStream {
+    let krate_span = krate.clone().into_iter().last().unwrap().span();
     let mut buf = vec![
         TokenTree::Punct(Punct::new('@', Spacing::Alone)),
         TokenTree::Ident(Ident::new(method, krate_span)),
     ];
-    buf.extend(input.into_iter());
+    buf.extend(input);
     let buf = vec![
-        TokenTree::Ident(krate),
         TokenTree::Punct(Punct::new(':', Spacing::Joint)),
         TokenTree::Punct(Punct::new(':', Spacing::Alone)),
         TokenTree::Ident(Ident::new("require_envs", krate_span)),
         TokenTree::Punct(Punct::new('!', Spacing::Alone)),
         TokenTree::Group(Group::new(Delimiter::Brace, buf.into_iter().collect())),
     ];
-    buf.into_iter().collect()
+    krate.into_iter().chain(buf).collect()
 }
 
 #[proc_macro]
 pub fn assert_var_body(input: TokenStream) -> TokenStream {
-    let stream = Stream::parse(input);
+    let (source, stream) = parse_with_arg(input);
     stream
         .decls
         .into_iter()
-        .map(|decl| call_require_envs(stream.krate.clone(), "assert", decl.tokens))
+        .map(|decl| call_require_envs(stream.krate.clone(), "assert", with_source(&source, &decl)))
         .collect()
 }
 
 #[proc_macro]
 pub fn any_set_body(input: TokenStream) -> TokenStream {
-    let stream = Stream::parse(input);
+    let (source, stream) = parse_with_arg(input);
     let stream = stream
         .decls
         .into_iter()
-        .map(|decl| call_require_envs(stream.krate.clone(), "get_res", decl.tokens))
+        .map(|decl| call_require_envs(stream.krate.clone(), "get_res", with_source(&source, &decl)))
         .flat_map(|x| [x, TokenStream::from_str(",").unwrap()])
         .collect();
     TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
@@ -106,7 +205,19 @@ pub fn help_body(input: TokenStream) -> TokenStream {
     let stream = stream
         .decls
         .into_iter()
-        .map(|decl| call_require_envs(stream.krate.clone(), "etext", decl.tokens))
+        .map(|decl| call_require_envs(stream.krate.clone(), "etext", decl.to_tokens()))
+        .flat_map(|x| [x, TokenStream::from_str(",").unwrap()])
+        .collect();
+    TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
+}
+
+#[proc_macro]
+pub fn check_body(input: TokenStream) -> TokenStream {
+    let (source, stream) = parse_with_arg(input);
+    let stream = stream
+        .decls
+        .into_iter()
+        .map(|decl| call_require_envs(stream.krate.clone(), "check", with_source(&source, &decl)))
         .flat_map(|x| [x, TokenStream::from_str(",").unwrap()])
         .collect();
     TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
@@ -114,30 +225,242 @@ pub fn help_body(input: TokenStream) -> TokenStream {
 
 #[proc_macro]
 pub fn getters(input: TokenStream) -> TokenStream {
+    let (pairs, stream) = parse_with_arg(input);
+    let TokenTree::Group(pairs) = pairs else {
+        panic!()
+    };
+    let source = default_source(&stream.krate, &pairs);
+    stream
+        .decls
+        .into_iter()
+        .flat_map(|decl| {
+            let mut tokens = vec![TokenTree::Ident(from_name(&decl.getter_name))];
+            tokens.extend(decl.to_tokens());
+            let method = if decl.cached { "cached_func" } else { "func" };
+            let mut sourced = vec![source.clone()];
+            sourced.extend(tokens.iter().cloned());
+            [
+                call_require_envs(stream.krate.clone(), method, sourced),
+                call_require_envs(stream.krate.clone(), "from_func", tokens),
+            ]
+        })
+        .collect()
+}
+
+/// Calls each cached getter, so that the assert function fills their caches.
+#[proc_macro]
+pub fn prime_body(input: TokenStream) -> TokenStream {
     let stream = Stream::parse(input);
     stream
         .decls
         .into_iter()
-        .map(|decl| call_require_envs(stream.krate.clone(), "func", decl.tokens))
+        .filter(|decl| decl.cached)
+        .map(|decl| call_require_envs(stream.krate.clone(), "prime", decl.to_tokens()))
         .collect()
 }
 
+/// Generates the assert and any set functions, along with the additional functions (and statics)
+/// requested by `key = name` pairs following the main three names in a `require_envs!` invocation.
+///
+/// Each of these functions which reads env vars, besides the snapshot function,
+/// gets a `_from` variant, which reads from a given `Source`.
+///
+/// The names of every generated function come first in the input of the other proc macros too,
+/// so that declarations whose getters would clash with them can be left out.
+#[proc_macro]
+pub fn extras(input: TokenStream) -> TokenStream {
+    let mut input = input.into_iter();
+    let krate = input.next().unwrap();
+    let TokenTree::Ident(_) = krate else { panic!() };
+    let krate = TokenStream::from(krate);
+    // require_envs! always passes the lists of pairs as parenthesized groups,
+    // and has already made sure they're lists of `ident = ident` pairs.
+    // The first holds the functions every invocation generates, and the second the user's extras.
+    let Some(TokenTree::Group(builtins)) = input.next() else {
+        panic!()
+    };
+    let Some(TokenTree::Group(pairs)) = input.next() else {
+        panic!()
+    };
+    let rest: Vec<TokenTree> = input.collect();
+    let source = default_source(&krate, &pairs);
+
+    let mut output = Vec::new();
+    let mut snapshot = false;
+    let builtins = pairs_of(builtins).map(|(key, name)| {
+        let method = match key.to_string().as_str() {
+            "assert" => "assert_fn",
+            "any_set" => "any_set_fn",
+            _ => unreachable!(),
+        };
+        Ok((key, method, name))
+    });
+    let extras = pairs_of(pairs).map(|(key, name)| match key.to_string().as_str() {
+        "try_assert" => Ok((key, "try_assert_fn", name)),
+        "status" => Ok((key, "status_fn", name)),
+        "vars" => Ok((key, "vars_static", name)),
+        "help_table" => Ok((key, "help_table_fn", name)),
+        "help_markdown" => Ok((key, "help_markdown_fn", name)),
+        "help_roff" => Ok((key, "help_roff_fn", name)),
+        "json_schema" => Ok((key, "json_schema_fn", name)),
+        "env_example" => Ok((key, "env_example_fn", name)),
+        "snapshot" if !std::mem::replace(&mut snapshot, true) => Ok((key, "snapshot_fn", name)),
+        "snapshot" => Err(compile_error(
+            "only one snapshot function can be generated",
+            key.span(),
+        )),
+        _ => Err(compile_error(
+            &format!("unknown generated function kind: `{key}`"),
+            key.span(),
+        )),
+    });
+    for pair in builtins.chain(extras) {
+        let (key, method, name) = match pair {
+            Ok(x) => x,
+            Err(e) => {
+                output.push(e);
+                continue;
+            }
+        };
+        let mut args = if !has_from_variant(&key.to_string()) {
+            vec![TokenTree::Ident(name)]
+        } else {
+            let from = from_name(&name);
+            vec![
+                source.clone(),
+                TokenTree::Ident(name),
+                TokenTree::Ident(from),
+            ]
+        };
+        args.push(TokenTree::Punct(Punct::new(';', Spacing::Alone)));
+        args.extend(rest.iter().cloned());
+        output.push(call_require_envs(krate.clone(), method, args));
+    }
+    output.into_iter().collect()
+}
+
+fn pairs_of(pairs: Group) -> impl Iterator<Item = (Ident, Ident)> {
+    let mut pairs = pairs.stream().into_iter();
+    std::iter::from_fn(move || loop {
+        let TokenTree::Ident(key) = pairs.next()? else {
+            continue;
+        };
+        let _eq = pairs.next();
+        let Some(TokenTree::Ident(name)) = pairs.next() else {
+            return None;
+        };
+        return Some((key, name));
+    })
+}
+
+/// A `VarStatus` for each declaration, as an array.
+#[proc_macro]
+pub fn status_body(input: TokenStream) -> TokenStream {
+    let (source, stream) = parse_with_arg(input);
+    let comma = || TokenTree::Punct(Punct::new(',', Spacing::Alone));
+    let stream = stream
+        .decls
+        .into_iter()
+        .flat_map(|decl| {
+            let mut lookup = vec![source.clone(), comma()];
+            lookup.extend(decl.secret_attr());
+            lookup.extend([TokenTree::Literal(decl.var_name.clone()), comma()]);
+            match decl.getter_mark {
+                // Status only needs to know whether the list is set, so we don't turn it into a `Vec`.
+                Mark::List(_) => {
+                    let mut list = stream.krate.clone();
+                    list.extend(TokenStream::from_str("::List<").unwrap());
+                    list.extend(decl.getter_type.iter().cloned());
+                    list.extend(TokenStream::from_str(">").unwrap());
+                    lookup.extend(list);
+                }
+                _ => lookup.extend(decl.getter_type.iter().cloned()),
+            }
+            lookup.extend([comma(), TokenTree::Literal(decl.help_message.clone())]);
+            let mut status = stream.krate.clone();
+            status.extend(TokenStream::from_str("::__private::var_status").unwrap());
+            status.extend([TokenTree::Group(Group::new(
+                Delimiter::Parenthesis,
+                [
+                    call_require_envs(stream.krate.clone(), "info", decl.to_tokens()),
+                    comma().into(),
+                    call_require_envs(stream.krate.clone(), "lookup", lookup),
+                ]
+                .into_iter()
+                .collect(),
+            ))]);
+            [status, comma().into()]
+        })
+        .collect();
+    TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
+}
+
+/// A `VarInfo` for each declaration, as an array.
+#[proc_macro]
+pub fn info_body(input: TokenStream) -> TokenStream {
+    let stream = Stream::parse(input);
+    let stream = stream
+        .decls
+        .into_iter()
+        .map(|decl| call_require_envs(stream.krate.clone(), "info", decl.to_tokens()))
+        .flat_map(|x| [x, TokenStream::from_str(",").unwrap()])
+        .collect();
+    TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
+}
+
+/// The names of every declared env var, as an array of string literals.
+#[proc_macro]
+pub fn var_names(input: TokenStream) -> TokenStream {
+    names_where(input, |_| true)
+}
+
+/// The names of every env var declared secret, as an array of string literals.
+#[proc_macro]
+pub fn secret_names(input: TokenStream) -> TokenStream {
+    names_where(input, |decl| decl.secret)
+}
+
+fn names_where(input: TokenStream, filter: fn(&VarDecl) -> bool) -> TokenStream {
+    let stream = Stream::parse(input);
+    let stream = stream
+        .decls
+        .into_iter()
+        .filter(filter)
+        .flat_map(|decl| {
+            [
+                TokenTree::Literal(decl.var_name),
+                TokenTree::Punct(Punct::new(',', Spacing::Alone)),
+            ]
+        })
+        .collect();
+    TokenTree::Group(Group::new(Delimiter::Bracket, stream)).into()
+}
+
+/// See the documentation of its re-export from `menv`.
+#[proc_macro_derive(FromEnv, attributes(menv))]
+pub fn from_env(input: TokenStream) -> TokenStream {
+    derive::from_env(input)
+}
+
 #[proc_macro]
 pub fn errors(input: TokenStream) -> TokenStream {
     let stream = Stream::parse(input);
-    stream.errors.into_iter().collect()
+    stream.errors.into_iter().chain(stream.warnings).collect()
 }
 
 fn compile_error(text: &str, span: Span) -> TokenStream {
-    let toks = TokenStream::from_str(&format!("\"{text}\"")).unwrap();
-    let toks = toks
-        .into_iter()
+    let toks = TokenStream::from_str("::core::compile_error!").unwrap();
+    let message = TokenTree::Literal(Literal::string(text));
+    toks.into_iter()
+        .chain([TokenTree::Group(Group::new(
+            Delimiter::Brace,
+            message.into(),
+        ))])
         .map(|mut tok| {
             tok.set_span(span);
             tok
         })
-        .collect();
-    toks
+        .collect()
 }
 
 #[proc_macro]
@@ -184,3 +507,265 @@ pub fn trimmed_help(input: TokenStream) -> TokenStream {
     output.extend(errors);
     output.into_iter().collect()
 }
+
+/// Turns a type into a string literal holding its source code, formatted like rustfmt would.
+///
+/// `stringify!` would separate every token with a space,
+/// since the type reaches `require_envs!` through our other proc macros.
+#[proc_macro]
+pub fn type_name(input: TokenStream) -> TokenStream {
+    let mut text = String::new();
+    write_type(&mut text, flatten_groups(input.into_iter().collect()));
+    TokenTree::Literal(Literal::string(&text)).into()
+}
+
+fn write_type(text: &mut String, tokens: Vec<TokenTree>) {
+    // Whether the last token was a word, which needs a space between it and another word.
+    let mut after_word = false;
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(tree) = tokens.next() {
+        match tree {
+            // Types named by the `require_envs!` internals are written without the `$crate::` they start with.
+            TokenTree::Ident(ident) if ident.to_string() == "$crate" => {
+                while matches!(tokens.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == ':')
+                {
+                    tokens.next();
+                }
+                continue;
+            }
+            TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                if after_word {
+                    text.push(' ');
+                }
+                *text += &tree.to_string();
+                after_word = true;
+                continue;
+            }
+            TokenTree::Punct(punct) => match punct.as_char() {
+                ',' | ';' => {
+                    text.push(punct.as_char());
+                    text.push(' ');
+                }
+                '+' | '=' => {
+                    text.push(' ');
+                    text.push(punct.as_char());
+                    text.push(' ');
+                }
+                // A lifetime's quote is joined to its name.
+                '\'' => {
+                    text.push('\'');
+                    after_word = false;
+                    continue;
+                }
+                c => text.push(c),
+            },
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{", "}"),
+                    Delimiter::None => ("", ""),
+                };
+                *text += open;
+                write_type(text, group.stream().into_iter().collect());
+                *text += close;
+            }
+        }
+        after_word = false;
+    }
+}
+
+/// Turns the expression given as a default value into a string literal holding its source code,
+/// exactly as it was written.
+///
+/// Falls back to the way `stringify!` would write it when the source code isn't available,
+/// like when the expression was produced by another macro.
+#[proc_macro]
+pub fn expr_text(input: TokenStream) -> TokenStream {
+    let tokens = flatten_groups(input.into_iter().collect());
+    let text = source_text(&tokens)
+        .unwrap_or_else(|| tokens.into_iter().collect::<TokenStream>().to_string());
+    TokenTree::Literal(Literal::string(&text)).into()
+}
+
+/// The source code of a sequence of tokens, with the whitespace between them collapsed to single spaces.
+///
+/// The span of the whole sequence belongs to the `macro_rules!` fragment which matched it,
+/// so we put together the source text of each token, and find the gaps between them from their positions.
+fn source_text(tokens: &[TokenTree]) -> Option<String> {
+    let mut text = String::new();
+    let mut last_end = None;
+    write_source_text(&mut text, &mut last_end, tokens)?;
+    Some(text)
+}
+
+fn write_source_text(
+    text: &mut String,
+    last_end: &mut Option<Span>,
+    tokens: &[TokenTree],
+) -> Option<()> {
+    for tree in tokens {
+        match tree {
+            // Groups are written a token at a time too, so line breaks inside them are collapsed.
+            TokenTree::Group(group) if group.delimiter() != Delimiter::None => {
+                write_token_text(text, last_end, group.span_open())?;
+                let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                write_source_text(text, last_end, &inner)?;
+                write_token_text(text, last_end, group.span_close())?;
+            }
+            TokenTree::Group(group) => {
+                let inner: Vec<TokenTree> = group.stream().into_iter().collect();
+                write_source_text(text, last_end, &inner)?;
+            }
+            tree => write_token_text(text, last_end, tree.span())?,
+        }
+    }
+    Some(())
+}
+
+/// Write the source text of a single token, after a space if it doesn't directly follow the last one.
+fn write_token_text(text: &mut String, last_end: &mut Option<Span>, span: Span) -> Option<()> {
+    if let Some(end) = last_end {
+        let start = span.start();
+        if (start.line(), start.column()) != (end.line(), end.column()) {
+            text.push(' ');
+        }
+    }
+    *text += &span.source_text()?;
+    *last_end = Some(span.end());
+    Some(())
+}
+
+/// Turns a literal default value into the text which its type's `FromStr` implementation
+/// should parse, as though the env var had been set to it.
+///
+/// When the declared type is a primitive, we can run that same `FromStr` implementation
+/// right here, so bad defaults are reported at compile time instead of at first use.
+#[proc_macro]
+pub fn default_text(input: TokenStream) -> TokenStream {
+    // `require_envs!` passes either `$default` or `$ty, $default`,
+    // where `$default` matched `$default:literal`.
+    // Only the latter is validated, so each mistake is only reported once.
+    let mut input: Vec<TokenTree> = input.into_iter().collect();
+    let default = flatten_groups(input.split_off(input.len() - 1));
+    let ty = flatten_groups(input.into_iter().take(1).collect());
+    let primitive = match &ty[..] {
+        [TokenTree::Ident(ident)] => {
+            Some(ident.to_string()).filter(|ty| PRIMITIVES.contains(&&**ty))
+        }
+        _ => None,
+    };
+
+    let (negative, lit) = match &default[..] {
+        [TokenTree::Punct(punct), TokenTree::Literal(lit)] if punct.as_char() == '-' => {
+            (true, lit_parse::Literal::parse(lit))
+        }
+        [TokenTree::Literal(lit)] => (false, lit_parse::Literal::parse(lit)),
+        [TokenTree::Ident(ident)] => match lit_parse::Literal::from_ident(ident) {
+            Some(lit) => (
+                false,
+                Ok(lit_parse::Output {
+                    x: lit,
+                    errors: Vec::new(),
+                }),
+            ),
+            None => unreachable!("internals failed to only pass a literal default"),
+        },
+        _ => unreachable!("internals failed to only pass a literal default"),
+    };
+    let lit = match lit {
+        Ok(lit) if lit.errors.is_empty() => lit.x,
+        Ok(lit) => return lit.errors.into_iter().collect(),
+        Err(e) => return e.into_iter().collect(),
+    };
+    let sign = if negative { "-" } else { "" };
+    let (text, suffix) = match lit.data() {
+        lit_parse::LiteralData::String(s) => (s.clone(), None),
+        lit_parse::LiteralData::Int(x, suffix) => (format!("{sign}{x}"), suffix.as_ref()),
+        lit_parse::LiteralData::Float(x, suffix) => (format!("{sign}{x}"), suffix.as_ref()),
+        lit_parse::LiteralData::Bool(x) => (x.to_string(), None),
+        lit_parse::LiteralData::Char(x) => (x.to_string(), None),
+        lit_parse::LiteralData::Unknown if ty.is_empty() => {
+            (default.last().unwrap().to_string(), None)
+        }
+        lit_parse::LiteralData::Unknown => {
+            return compile_error(
+                "unsupported kind of literal for a default value",
+                lit.span(),
+            )
+        }
+    };
+
+    if let Some(primitive) = primitive {
+        if let Some(suffix) = suffix.filter(|&suffix| *suffix != primitive) {
+            return compile_error(
+                &format!(
+                    "default value has suffix `{suffix}`, but the declared type is `{primitive}`"
+                ),
+                lit.span(),
+            );
+        }
+        if negative && primitive.starts_with('u') {
+            return compile_error(
+                &format!(
+                    "default value is negative, but the declared type `{primitive}` is unsigned"
+                ),
+                lit.span(),
+            );
+        }
+        if let Err(e) = parse_primitive(&primitive, &text) {
+            return compile_error(
+                &format!("default value is not a valid `{primitive}`: {e}"),
+                lit.span(),
+            );
+        }
+    }
+    TokenTree::Literal(Literal::string(&text)).into()
+}
+
+const PRIMITIVES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64", "bool", "char",
+];
+
+/// Run the `FromStr` implementation of the named primitive type.
+fn parse_primitive(ty: &str, text: &str) -> Result<(), String> {
+    fn parse<T: FromStr>(text: &str) -> Result<(), String>
+    where
+        T::Err: std::fmt::Display,
+    {
+        text.parse::<T>().map(drop).map_err(|e| e.to_string())
+    }
+    match ty {
+        "u8" => parse::<u8>(text),
+        "u16" => parse::<u16>(text),
+        "u32" => parse::<u32>(text),
+        "u64" => parse::<u64>(text),
+        "u128" => parse::<u128>(text),
+        // These are checked against the widest `usize` and `isize` any target has,
+        // since we don't know which target we're expanding for.
+        "usize" => parse::<u64>(text),
+        "isize" => parse::<i64>(text),
+        "i8" => parse::<i8>(text),
+        "i16" => parse::<i16>(text),
+        "i32" => parse::<i32>(text),
+        "i64" => parse::<i64>(text),
+        "i128" => parse::<i128>(text),
+        "f32" => parse::<f32>(text),
+        "f64" => parse::<f64>(text),
+        "bool" => parse::<bool>(text),
+        "char" => parse::<char>(text),
+        _ => Ok(()),
+    }
+}
+
+/// Strip away the invisible groups `macro_rules!` wraps around forwarded fragments.
+fn flatten_groups(mut trees: Vec<TokenTree>) -> Vec<TokenTree> {
+    while let [TokenTree::Group(group)] = &trees[..] {
+        if group.delimiter() != Delimiter::None {
+            break;
+        }
+        trees = group.stream().into_iter().collect();
+    }
+    trees
+}
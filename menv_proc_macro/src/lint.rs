@@ -0,0 +1,132 @@
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use proc_macro::{Ident, Span, TokenStream, TokenTree};
+
+use crate::decl::VarDecl;
+use crate::options::{Level, Options};
+use crate::{compile_error, from_name};
+
+/// Check a list of declarations for mistakes which are well-formed Rust,
+/// but which we know can't be what the user meant.
+///
+/// Declarations which would cause errors further down the line are removed.
+///
+/// `functions` holds the names of the other functions a `require_envs!` invocation generates,
+/// which getters and their `_from` variants mustn't clash with. It is `None` for the fields
+/// of a `FromEnv` struct, which don't get getters.
+pub fn lint(
+    decls: &mut Vec<VarDecl>,
+    options: &Options,
+    functions: Option<&[Ident]>,
+    errors: &mut Vec<TokenStream>,
+    warnings: &mut Vec<TokenStream>,
+) {
+    let mut var_names = HashSet::new();
+    let mut getter_names = HashSet::new();
+    // What each name which is already taken is the name of.
+    let mut taken: HashMap<String, String> = functions
+        .into_iter()
+        .flatten()
+        .map(|name| (name.to_string(), format!("the generated function `{name}`")))
+        .collect();
+    decls.retain(|decl| {
+        let name = &decl.var_name_value;
+        let span = decl.var_name.span();
+        if name.is_empty() {
+            errors.push(compile_error("env var name is empty", span));
+        } else if name.contains('=') {
+            errors.push(compile_error(
+                "env var name contains `=`, so it can never be set",
+                span,
+            ));
+        } else if name.contains('\0') {
+            errors.push(compile_error(
+                "env var name contains a NUL character, so it can never be set",
+                span,
+            ));
+        } else if !is_upper_snake_case(name) {
+            let message = format!("env var name `{name}` is not UPPER_SNAKE_CASE");
+            match options.non_upper_snake_case {
+                Level::Allow => {}
+                Level::Warn => warnings.push(warning(&message, span)),
+                Level::Deny => errors.push(compile_error(&message, span)),
+            }
+        }
+        if !var_names.insert(name.clone()) {
+            errors.push(compile_error(
+                &format!("env var `{name}` is declared more than once"),
+                span,
+            ));
+        }
+        // Two functions with the same name would get rustc to complain too,
+        // so we leave out the second one, and only complain ourselves.
+        let getter = decl.getter_name.to_string();
+        if !getter_names.insert(getter.clone()) {
+            errors.push(compile_error(
+                &format!("getter `{getter}` is declared more than once"),
+                decl.getter_name.span(),
+            ));
+            return false;
+        }
+        if functions.is_some() {
+            let from = from_name(&decl.getter_name).to_string();
+            let clash = match (taken.get(&getter), taken.get(&from)) {
+                (Some(other), _) => Some(format!("getter `{getter}` has the same name as {other}")),
+                (None, Some(other)) => Some(format!(
+                    "the `_from` variant of getter `{getter}`, `{from}`, has the same name as {other}"
+                )),
+                (None, None) => None,
+            };
+            if let Some(message) = clash {
+                errors.push(compile_error(&message, decl.getter_name.span()));
+                return false;
+            }
+            taken.insert(getter.clone(), format!("the getter `{getter}`"));
+            taken.insert(from, format!("the `_from` variant of getter `{getter}`"));
+        }
+        true
+    });
+}
+
+fn is_upper_snake_case(name: &str) -> bool {
+    !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+}
+
+/// Proc macros can't emit warnings on stable,
+/// so we get rustc to do it for us by using a deprecated item.
+fn warning(text: &str, span: Span) -> TokenStream {
+    let toks = TokenStream::from_str(&format!(
+        "const _: () = {{
+            #[deprecated(note = {text:?})]
+            #[allow(non_camel_case_types)]
+            struct menv_lint;
+            let _ = menv_lint;
+        }};"
+    ))
+    .unwrap();
+    toks.into_iter()
+        .map(|tok| match tok {
+            // Point the warning at the offending span, rather than at the macro invocation.
+            TokenTree::Group(group) => {
+                let stream = group
+                    .stream()
+                    .into_iter()
+                    .map(|tok| match tok {
+                        TokenTree::Ident(ident) if ident.to_string() == "menv_lint" => {
+                            TokenTree::Ident(Ident::new("menv_lint", span))
+                        }
+                        tok => tok,
+                    })
+                    .collect();
+                let mut new = proc_macro::Group::new(group.delimiter(), stream);
+                new.set_span(group.span());
+                TokenTree::Group(new)
+            }
+            tok => tok,
+        })
+        .collect()
+}
@@ -20,6 +20,12 @@ impl Literal {
 #[non_exhaustive]
 pub enum LiteralData {
     String(String),
+    /// The value of an integer literal, with its suffix (if any) stripped off.
+    Int(u128, Option<String>),
+    /// The text of a float literal, with underscores and its suffix (if any) stripped off.
+    Float(String, Option<String>),
+    Bool(bool),
+    Char(char),
     Unknown,
 }
 
@@ -27,25 +33,40 @@ impl Literal {
     pub fn parse(lit: &proc_macro::Literal) -> Result<Output<Self>, Vec<TokenStream>> {
         let mut errors = Vec::new();
         let lit_str = lit.to_string();
-        let lit = if lit_str.starts_with("\"") {
+        let data = if lit_str.starts_with('"') {
             let s = parse_str_literal(&lit_str, lit.span())?;
             errors.extend(s.errors);
-            Self {
-                data: LiteralData::String(s.x),
-                span: lit.span(),
-            }
-        } else if lit_str.starts_with("r") {
-            Self {
-                data: LiteralData::String(parse_raw_str_literal(&lit_str)),
-                span: lit.span(),
-            }
+            LiteralData::String(s.x)
+        } else if lit_str.starts_with('r') {
+            LiteralData::String(parse_raw_str_literal(&lit_str))
+        } else if lit_str.starts_with('\'') {
+            let c = parse_char_literal(&lit_str, lit.span())?;
+            errors.extend(c.errors);
+            c.x
+        } else if lit_str.starts_with(|c: char| c.is_ascii_digit()) {
+            parse_number_literal(&lit_str, lit.span())?
         } else {
+            LiteralData::Unknown
+        };
+        Ok(Output::new(
             Self {
-                data: LiteralData::Unknown,
+                data,
                 span: lit.span(),
-            }
+            },
+            errors,
+        ))
+    }
+    /// `true` and `false` are keywords, not literal tokens, but `$x:literal` accepts them.
+    pub fn from_ident(ident: &proc_macro::Ident) -> Option<Self> {
+        let data = match ident.to_string().as_str() {
+            "true" => LiteralData::Bool(true),
+            "false" => LiteralData::Bool(false),
+            _ => return None,
         };
-        Ok(Output::new(lit, errors))
+        Some(Self {
+            data,
+            span: ident.span(),
+        })
     }
 }
 
@@ -99,7 +120,7 @@ fn parse_str_literal(input: &str, span: Span) -> Result<Output<String>, Vec<Toke
                         state = State::Normal
                     }
                     '\'' => {
-                        text.push('"');
+                        text.push('\'');
                         state = State::Normal
                     }
                     // - ASCII_ESCAPE
@@ -241,7 +262,7 @@ fn parse_raw_str_literal(input: &str) -> String {
             [b'"', ..] => {
                 let mut hash_suffix_count = 0;
                 'attempt_termination: loop {
-                    let offset: usize = (hash_suffix_count + 1).into();
+                    let offset: usize = hash_suffix_count + 1;
                     if hash_suffix_count == hash_prefix_count {
                         break 'string_body;
                     }
@@ -274,3 +295,97 @@ fn parse_raw_str_literal(input: &str) -> String {
     }
     text
 }
+
+/// See [the Reference](https://doc.rust-lang.org/1.91.1/reference/tokens.html#character-literals)
+/// for information about the syntax being parsed here.
+fn parse_char_literal(input: &str, span: Span) -> Result<Output<LiteralData>, Vec<TokenStream>> {
+    assert!(input.starts_with('\''));
+    let Some(end) = input.rfind('\'') else {
+        unreachable!()
+    };
+    if end + 1 != input.len() {
+        return Err(vec![compile_error(
+            "character literal suffixes are not supported",
+            span,
+        )]);
+    }
+    let body = &input[1..end];
+    // Escapes work the same way they do in string literals, so we reuse that parser.
+    // A lone double quote is the one thing that would confuse it, and needs no escaping anyway.
+    if body.starts_with('\\') {
+        let s = parse_str_literal(&format!("\"{body}\""), span)?;
+        let c = s.x.chars().next().unwrap();
+        Ok(Output::new(LiteralData::Char(c), s.errors))
+    } else {
+        let c = body.chars().next().unwrap();
+        Ok(Output::new(LiteralData::Char(c), Vec::new()))
+    }
+}
+
+const INT_SUFFIXES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize",
+];
+const FLOAT_SUFFIXES: &[&str] = &["f32", "f64"];
+
+/// See [the Reference](https://doc.rust-lang.org/1.91.1/reference/tokens.html#number-literals)
+/// for information about the syntax being parsed here.
+fn parse_number_literal(input: &str, span: Span) -> Result<LiteralData, Vec<TokenStream>> {
+    let (radix, body) = match input.as_bytes() {
+        [b'0', b'x', ..] => (16, &input[2..]),
+        [b'0', b'o', ..] => (8, &input[2..]),
+        [b'0', b'b', ..] => (2, &input[2..]),
+        _ => (10, input),
+    };
+    // Hex digits include `e` and `f`, so hex literals can only ever be integers,
+    // and their suffixes must start with something which isn't a hex digit.
+    let digits_end = body
+        .find(|c: char| !(c.is_digit(radix) || c == '_'))
+        .unwrap_or(body.len());
+    let (digits, rest) = body.split_at(digits_end);
+    let is_float = radix == 10
+        && (rest.starts_with('.')
+            || rest.starts_with(['e', 'E'])
+            || FLOAT_SUFFIXES.contains(&rest));
+    if is_float {
+        let (text, suffix) = match FLOAT_SUFFIXES
+            .iter()
+            .find(|&&suffix| input.ends_with(suffix))
+        {
+            Some(suffix) => (
+                &input[..input.len() - suffix.len()],
+                Some(suffix.to_string()),
+            ),
+            None => (input, None),
+        };
+        if text.contains(|c: char| c.is_alphabetic() && !matches!(c, 'e' | 'E')) {
+            return Err(vec![compile_error(
+                "unsupported float literal suffix",
+                span,
+            )]);
+        }
+        return Ok(LiteralData::Float(text.replace('_', ""), suffix));
+    }
+    let suffix = parse_suffix(rest, INT_SUFFIXES, span)?;
+    let digits = digits.replace('_', "");
+    match u128::from_str_radix(&digits, radix) {
+        Ok(x) => Ok(LiteralData::Int(x, suffix)),
+        Err(_) => Err(vec![compile_error("integer literal is too large", span)]),
+    }
+}
+
+fn parse_suffix(
+    suffix: &str,
+    allowed: &[&str],
+    span: Span,
+) -> Result<Option<String>, Vec<TokenStream>> {
+    if suffix.is_empty() {
+        Ok(None)
+    } else if allowed.contains(&suffix) {
+        Ok(Some(suffix.to_string()))
+    } else {
+        Err(vec![compile_error(
+            &format!("unsupported literal suffix: `{suffix}`"),
+            span,
+        )])
+    }
+}
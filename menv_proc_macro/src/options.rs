@@ -0,0 +1,106 @@
+use std::iter::Peekable;
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+
+use crate::compile_error;
+
+/// Settings for a whole `require_envs!` invocation,
+/// given as inner attributes (`#![...]`) before the first declaration.
+#[derive(Default)]
+pub struct Options {
+    pub non_upper_snake_case: Level,
+    /// Whether `#![cached]` was given, making every getter cached.
+    pub cached: bool,
+}
+
+/// How a lint which is off by default should be reported.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    #[default]
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl Options {
+    /// Consume any inner attributes at the front of `input`,
+    /// leaving the outer attributes of the first declaration alone.
+    pub fn parse<I: Iterator<Item = TokenTree> + Clone>(
+        input: &mut Peekable<I>,
+        errors: &mut Vec<TokenStream>,
+    ) -> Self {
+        let mut options = Self::default();
+        while matches!(input.peek(), Some(TokenTree::Punct(punct)) if punct.as_char() == '#') {
+            let mut ahead = input.clone();
+            ahead.next();
+            if matches!(ahead.next(), Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Bracket)
+            {
+                break;
+            }
+            let pound = input.next().unwrap();
+            let bang = input.next();
+            let body = match (&bang, input.next()) {
+                (Some(TokenTree::Punct(bang)), Some(TokenTree::Group(body)))
+                    if bang.as_char() == '!' && body.delimiter() == Delimiter::Bracket =>
+                {
+                    body
+                }
+                _ => {
+                    errors.push(compile_error(
+                        "expected an inner attribute, like `#![warn(non_upper_snake_case)]`",
+                        pound.span(),
+                    ));
+                    continue;
+                }
+            };
+            options.apply(body.stream(), errors);
+        }
+        options
+    }
+
+    fn apply(&mut self, attr: TokenStream, errors: &mut Vec<TokenStream>) {
+        let mut attr = attr.into_iter();
+        let Some(TokenTree::Ident(name)) = attr.next() else {
+            return errors.push(compile_error(
+                "expected an attribute name",
+                proc_macro::Span::call_site(),
+            ));
+        };
+        let level = match name.to_string().as_str() {
+            "cached" => {
+                if let Some(extra) = attr.next() {
+                    errors.push(compile_error("`cached` takes no arguments", extra.span()));
+                }
+                self.cached = true;
+                return;
+            }
+            "allow" => Level::Allow,
+            "warn" => Level::Warn,
+            "deny" => Level::Deny,
+            _ => {
+                return errors.push(compile_error(
+                    &format!("unknown attribute: `{name}`"),
+                    name.span(),
+                ))
+            }
+        };
+        let Some(TokenTree::Group(lints)) = attr.next() else {
+            return errors.push(compile_error(
+                &format!("expected a list of lints, like `{name}(non_upper_snake_case)`"),
+                name.span(),
+            ));
+        };
+        for lint in lints.stream() {
+            match lint {
+                TokenTree::Ident(lint) if lint.to_string() == "non_upper_snake_case" => {
+                    self.non_upper_snake_case = level
+                }
+                TokenTree::Punct(punct) if punct.as_char() == ',' => {}
+                lint => errors.push(compile_error(
+                    &format!("unknown lint: `{lint}`"),
+                    lint.span(),
+                )),
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A number of bytes, which can be parsed from text like `512MiB`, `2G` or `64k`.
+///
+/// A size is written as a whole number, optionally followed by a unit.
+/// The SI prefixes `k`, `M`, `G`, `T`, `P` and `E` multiply by powers of 1000,
+/// while their IEC counterparts `Ki`, `Mi`, `Gi`, `Ti`, `Pi` and `Ei` multiply by powers of 1024.
+/// Either may be followed by `B`, and a `B` on its own means bytes, as does no unit at all.
+/// Units are matched regardless of case. Sizes which don't fit in a [`u64`] are rejected.
+///
+/// Its [`Display`](fmt::Display) implementation uses whichever unit gives the smallest whole number,
+/// like `512MiB` or `2GB`, so its output can always be parsed back.
+/// ```
+/// use std::collections::HashMap;
+/// use menv::ByteSize;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     cache_size = "512MiB", "MENV_DOC_BYTES_CACHE", ByteSize,
+///     "MENV_DOC_BYTES_CACHE, if set, overrides how much memory to use for caching";
+///
+///     body_limit!, "MENV_DOC_BYTES_BODY_LIMIT", ByteSize,
+///     "MENV_DOC_BYTES_BODY_LIMIT should be set to the largest request body to accept";
+/// }
+///
+/// let vars = HashMap::from([("MENV_DOC_BYTES_BODY_LIMIT", "64k")]);
+/// assert_eq!(*cache_size_from(&vars), 512 * 1024 * 1024);
+/// assert_eq!(cache_size_from(&vars).to_string(), "512MiB");
+/// assert_eq!(body_limit_from(&vars).unwrap(), ByteSize(64_000));
+/// assert_eq!("2g".parse::<ByteSize>().unwrap().to_string(), "2GB");
+/// assert_eq!(
+///     "20EiB".parse::<ByteSize>().unwrap_err().to_string(),
+///     "the size is too large to fit in a u64",
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+impl FromStr for ByteSize {
+    type Err = ParseByteSizeError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseByteSizeError::Empty);
+        }
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        if digits == 0 {
+            return Err(ParseByteSizeError::MissingNumber);
+        }
+        let count: u64 = s[..digits]
+            .parse()
+            .map_err(|_| ParseByteSizeError::TooLarge)?;
+        let unit = s[digits..].trim_start();
+        let size =
+            unit_size(unit).ok_or_else(|| ParseByteSizeError::UnknownUnit(unit.to_owned()))?;
+        count
+            .checked_mul(size)
+            .map(Self)
+            .ok_or(ParseByteSizeError::TooLarge)
+    }
+}
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (count, symbol) = UNITS
+            .iter()
+            .filter(|&&(_, size)| self.0.is_multiple_of(size))
+            .map(|&(symbol, size)| (self.0 / size, symbol))
+            .min()
+            .unwrap_or((0, "B"));
+        write!(f, "{count}{symbol}")
+    }
+}
+impl Deref for ByteSize {
+    type Target = u64;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl DerefMut for ByteSize {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl From<u64> for ByteSize {
+    fn from(bytes: u64) -> Self {
+        Self(bytes)
+    }
+}
+impl From<ByteSize> for u64 {
+    fn from(size: ByteSize) -> Self {
+        size.0
+    }
+}
+
+/// The symbol of every unit, as displayed, with the number of bytes in it.
+const UNITS: &[(&str, u64)] = &[
+    ("B", 1),
+    ("kB", 1000),
+    ("KiB", 1 << 10),
+    ("MB", 1000u64.pow(2)),
+    ("MiB", 1 << 20),
+    ("GB", 1000u64.pow(3)),
+    ("GiB", 1 << 30),
+    ("TB", 1000u64.pow(4)),
+    ("TiB", 1 << 40),
+    ("PB", 1000u64.pow(5)),
+    ("PiB", 1 << 50),
+    ("EB", 1000u64.pow(6)),
+    ("EiB", 1 << 60),
+];
+
+/// The number of bytes in a unit, written in any case, with or without its `B`.
+fn unit_size(unit: &str) -> Option<u64> {
+    let unit = unit.to_ascii_lowercase();
+    let unit = match unit.strip_suffix('b') {
+        // Neither `B` nor `iB` is a unit by itself.
+        Some("i") => return None,
+        Some(prefix) => prefix,
+        None => &unit,
+    };
+    let (prefix, iec) = match unit.strip_suffix('i') {
+        Some(prefix) => (prefix, true),
+        None => (unit, false),
+    };
+    let power = match prefix {
+        "" if !iec => 0,
+        "k" => 1,
+        "m" => 2,
+        "g" => 3,
+        "t" => 4,
+        "p" => 5,
+        "e" => 6,
+        _ => return None,
+    };
+    Some(if iec { 1024u64 } else { 1000 }.pow(power))
+}
+
+/// The ways in which parsing a [`ByteSize`] can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseByteSizeError {
+    /// The text is empty, or only whitespace.
+    Empty,
+    /// The text doesn't start with a number, as with `MiB`.
+    MissingNumber,
+    /// The unit isn't one of those understood.
+    UnknownUnit(String),
+    /// The size is larger than a [`u64`] can hold.
+    TooLarge,
+}
+impl fmt::Display for ParseByteSizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => f.write_str("expected a size, like `512MiB` or `2G`"),
+            Self::MissingNumber => f.write_str("expected a number before the unit, like `512MiB`"),
+            Self::UnknownUnit(unit) => write!(
+                f,
+                "unknown unit `{unit}`, expected a prefix like `k`, `M` or `Gi`, optionally followed by `B`"
+            ),
+            Self::TooLarge => f.write_str("the size is too large to fit in a u64"),
+        }
+    }
+}
+impl Error for ParseByteSizeError {}
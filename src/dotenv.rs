@@ -0,0 +1,369 @@
+//! Reading env vars from `.env` files, without touching the process environment.
+//!
+//! A [`Dotenv`] is a [`Source`], so it can be given to the `_from` variants of
+//! the functions generated by [`require_envs`](crate::require_envs).
+//! Combined with the process environment using [`Source::or`], it gives vars which are
+//! actually set precedence over those in the file, like most `.env` loaders do:
+//! ```
+//! use menv::dotenv::Dotenv;
+//! use menv::{ProcessEnv, Source};
+//!
+//! menv::require_envs! {
+//!     (assert_env_vars, any_set, gen_help);
+//!
+//!     server_port, "MENV_DOC_DOTENV_PORT", u16,
+//!     "MENV_DOC_DOTENV_PORT should be set to the desired server port";
+//!
+//!     motd, "MENV_DOC_DOTENV_MOTD", String,
+//!     "MENV_DOC_DOTENV_MOTD should be set to the message of the day";
+//! }
+//!
+//! let file: Dotenv = r#"
+//! ## Local settings
+//! export MENV_DOC_DOTENV_PORT=25565
+//! MENV_DOC_DOTENV_MOTD="Welcome!
+//! Be nice."
+//! "#
+//! .parse()
+//! .unwrap();
+//!
+//! let source = ProcessEnv.or(file);
+//! assert_env_vars_from(&source);
+//! assert_eq!(server_port_from(&source), 25565);
+//! assert_eq!(motd_from(&source), "Welcome!\nBe nice.");
+//! ```
+//!
+//! # Syntax
+//! Each line holds a single `NAME=value` assignment, optionally preceded by `export`.
+//! Blank lines, and lines starting with `#`, are ignored. Values may be:
+//! - Unquoted, in which case they run until the end of the line, or until a `#` preceded by whitespace,
+//!   which starts a comment. Surrounding whitespace is trimmed.
+//! - Single-quoted, in which case they're taken literally, and may span multiple lines.
+//! - Double-quoted, in which case they may span multiple lines, and the escapes
+//!   `\n`, `\r`, `\t`, `\"`, `\'`, `\\` and `\$` are processed.
+//!
+//! When a name is assigned more than once, the last assignment wins.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::iter::Peekable;
+use std::path::Path;
+use std::str::{Chars, FromStr};
+
+use crate::Source;
+
+/// The vars assigned in a `.env` file.
+#[derive(Clone, Debug, Default)]
+pub struct Dotenv {
+    vars: Vec<(String, String)>,
+}
+impl Dotenv {
+    /// Read and parse the file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Error> {
+        fs::read_to_string(path).map_err(Error::Io)?.parse()
+    }
+    /// The value assigned to `name`, if there is one.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars
+            .iter()
+            .find(|(var, _)| var == name)
+            .map(|(_, value)| &**value)
+    }
+    /// Every assignment, in the order the names first appear in the file.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.vars.iter().map(|(var, value)| (&**var, &**value))
+    }
+    fn set(&mut self, name: String, value: String) {
+        match self.vars.iter_mut().find(|(var, _)| *var == name) {
+            Some((_, old)) => *old = value,
+            None => self.vars.push((name, value)),
+        }
+    }
+}
+impl Source for Dotenv {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.get(name).map(OsString::from)
+    }
+}
+
+impl FromStr for Dotenv {
+    type Err = Error;
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: text.chars().peekable(),
+            line: 1,
+        };
+        let mut dotenv = Self::default();
+        while let Some((name, value)) = parser.assignment()? {
+            dotenv.set(name, value);
+        }
+        Ok(dotenv)
+    }
+}
+
+/// The ways in which loading a `.env` file can fail.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The file could not be read.
+    Io(io::Error),
+    /// The file is malformed.
+    Syntax {
+        /// The line the problem was found on, counting from 1.
+        line: usize,
+        message: String,
+    },
+}
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "could not read .env file: {e}"),
+            Self::Syntax { line, message } => write!(f, "line {line} of .env file: {message}"),
+        }
+    }
+}
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Syntax { .. } => None,
+        }
+    }
+}
+
+/// Write `value` the way it should appear after `NAME=` in a `.env` file, quoting it if needed.
+pub(crate) fn quote(value: &str) -> String {
+    let plain = |c: char| !c.is_whitespace() && !matches!(c, '#' | '"' | '\'' | '\\');
+    if !value.is_empty() && value.chars().all(plain) {
+        return value.to_owned();
+    }
+    let mut out = String::from('"');
+    for c in value.chars() {
+        match c {
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            '"' | '\\' | '$' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    line: usize,
+}
+impl Parser<'_> {
+    fn next(&mut self) -> Option<char> {
+        let c = self.chars.next()?;
+        if c == '\n' {
+            self.line += 1;
+        }
+        Some(c)
+    }
+    fn error(&self, line: usize, message: impl Into<String>) -> Error {
+        Error::Syntax {
+            line,
+            message: message.into(),
+        }
+    }
+    /// Skip spaces and tabs, but not newlines.
+    fn skip_blanks(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\t')) {
+            self.next();
+        }
+    }
+    /// Skip the rest of the line, including its newline.
+    fn skip_line(&mut self) {
+        while let Some(c) = self.next() {
+            if c == '\n' {
+                break;
+            }
+        }
+    }
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_' || c == '.') {
+                break;
+            }
+            name.push(c);
+            self.next();
+        }
+        name
+    }
+
+    /// Parse the next assignment, skipping blank lines and comments.
+    fn assignment(&mut self) -> Result<Option<(String, String)>, Error> {
+        loop {
+            self.skip_blanks();
+            match self.chars.peek() {
+                None => return Ok(None),
+                Some('\n' | '\r') => self.skip_line(),
+                Some('#') => self.skip_line(),
+                Some(_) => break,
+            }
+        }
+        let line = self.line;
+        let mut name = self.name();
+        if name == "export" && matches!(self.chars.peek(), Some(' ' | '\t')) {
+            self.skip_blanks();
+            name = self.name();
+        }
+        if name.is_empty() {
+            let found = self.chars.peek().copied().unwrap_or(' ');
+            return Err(self.error(line, format!("expected a variable name, found `{found}`")));
+        }
+        self.skip_blanks();
+        if self.chars.peek() != Some(&'=') {
+            return Err(self.error(line, format!("expected `=` after `{name}`")));
+        }
+        self.next();
+        self.skip_blanks();
+        let value = match self.chars.peek() {
+            Some('\'') => self.single_quoted(line)?,
+            Some('"') => self.double_quoted(line)?,
+            _ => return Ok(Some((name, self.unquoted()))),
+        };
+        // Only a comment can follow a quoted value.
+        self.skip_blanks();
+        match self.chars.peek() {
+            None | Some('\n' | '\r' | '#') => self.skip_line(),
+            Some(_) => {
+                return Err(self.error(
+                    self.line,
+                    format!("unexpected characters after the value of `{name}`"),
+                ))
+            }
+        }
+        Ok(Some((name, value)))
+    }
+
+    fn unquoted(&mut self) -> String {
+        let mut value = String::new();
+        while let Some(c) = self.next() {
+            match c {
+                '\n' => break,
+                '#' if value.ends_with([' ', '\t']) || value.is_empty() => {
+                    self.skip_line();
+                    break;
+                }
+                c => value.push(c),
+            }
+        }
+        value.trim_end().to_owned()
+    }
+
+    fn single_quoted(&mut self, line: usize) -> Result<String, Error> {
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('\'') => return Ok(value),
+                Some(c) => value.push(c),
+                None => return Err(self.error(line, "unterminated single-quoted value")),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self, line: usize) -> Result<String, Error> {
+        self.next();
+        let mut value = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => {
+                    let escape_line = self.line;
+                    value.push(match self.next() {
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some(c @ ('"' | '\'' | '\\' | '$')) => c,
+                        Some(c) => {
+                            return Err(
+                                self.error(escape_line, format!("unknown escape sequence `\\{c}`"))
+                            )
+                        }
+                        None => return Err(self.error(line, "unterminated double-quoted value")),
+                    });
+                }
+                Some(c) => value.push(c),
+                None => return Err(self.error(line, "unterminated double-quoted value")),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Dotenv, Error};
+
+    fn parse(text: &str) -> Dotenv {
+        text.parse().unwrap()
+    }
+    fn syntax_error_line(text: &str) -> usize {
+        match text.parse::<Dotenv>() {
+            Err(Error::Syntax { line, .. }) => line,
+            other => panic!("expected a syntax error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn syntax_errors_report_their_line() {
+        // An unterminated quote is reported where it was opened.
+        assert_eq!(syntax_error_line("A=1\nB=\"never\nclosed\n"), 2);
+        assert_eq!(syntax_error_line("A=1\n\nB='never\nclosed"), 3);
+        assert_eq!(syntax_error_line("A=1\n# comment\nB 2\n"), 3);
+        assert_eq!(syntax_error_line("A=\"1\" 2\n"), 1);
+        assert_eq!(syntax_error_line("A=\"multi\nline\" trailing\n"), 2);
+        assert_eq!(syntax_error_line("A=1\nB=\"\\q\"\n"), 2);
+    }
+
+    #[test]
+    fn single_quotes_are_literal() {
+        let dotenv = parse("A='$HOME \\n \"x\" # not a comment'\nB='two\nlines'");
+        assert_eq!(dotenv.get("A"), Some("$HOME \\n \"x\" # not a comment"));
+        assert_eq!(dotenv.get("B"), Some("two\nlines"));
+    }
+
+    #[test]
+    fn double_quotes_process_escapes() {
+        let dotenv = parse(concat!(r#"A="one\ntwo""#, "\n", r#"B="\$HOME \"x\" \\""#));
+        assert_eq!(dotenv.get("A"), Some("one\ntwo"));
+        assert_eq!(dotenv.get("B"), Some("$HOME \"x\" \\"));
+        assert!(matches!(
+            r#"A="\q""#.parse::<Dotenv>(),
+            Err(Error::Syntax { message, .. }) if message.contains("`\\q`")
+        ));
+    }
+
+    #[test]
+    fn comments_need_whitespace_before_them() {
+        let dotenv = parse("A=1 # comment\nB=2#3\nC=#comment\nD=4\t# comment");
+        assert_eq!(dotenv.get("A"), Some("1"));
+        assert_eq!(dotenv.get("B"), Some("2#3"));
+        assert_eq!(dotenv.get("C"), Some(""));
+        assert_eq!(dotenv.get("D"), Some("4"));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let dotenv = parse("# comment\r\nA=1\r\n\r\nB=\"2\"\r\nexport C='3' # comment\r\nD=\r\n");
+        let vars: Vec<_> = dotenv.iter().collect();
+        assert_eq!(vars, [("A", "1"), ("B", "2"), ("C", "3"), ("D", "")]);
+    }
+
+    #[test]
+    fn load_missing_file() {
+        let error = Dotenv::load("this/file/does/not/exist.env").unwrap_err();
+        assert!(matches!(&error, Error::Io(e) if e.kind() == std::io::ErrorKind::NotFound));
+        assert!(error.to_string().starts_with("could not read .env file: "));
+    }
+}
@@ -0,0 +1,257 @@
+//! Reading durations written like `250ms`, `30s` or `1h30m`.
+
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A [`std::time::Duration`] which can be parsed from text like `250ms`, `30s`, `5m` or `1h30m`.
+///
+/// A duration is written as one or more numbers, each followed by one of the units
+/// `ns`, `us` (or `µs`), `ms`, `s`, `m`, `h` and `d`, optionally separated by whitespace.
+/// A bare number, like `30`, is taken to be in the unit `U`, which is [`Seconds`] by default.
+///
+/// Its [`Display`](fmt::Display) implementation writes the duration in the same form,
+/// using the largest units possible, so its output can always be parsed back.
+/// ```
+/// use std::collections::HashMap;
+/// use menv::duration::Millis;
+/// use menv::Duration;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     timeout = "1m30s", "MENV_DOC_DURATION_TIMEOUT", Duration,
+///     "MENV_DOC_DURATION_TIMEOUT, if set, overrides how long to wait for a response";
+///
+///     poll_interval = "250", "MENV_DOC_DURATION_POLL", Duration<Millis>,
+///     "MENV_DOC_DURATION_POLL, if set, overrides how often to poll, in milliseconds by default";
+/// }
+///
+/// let vars = HashMap::from([("MENV_DOC_DURATION_TIMEOUT", "2h 5s")]);
+/// assert_eq!(*timeout_from(&vars), std::time::Duration::from_secs(7205));
+/// assert_eq!(timeout_from(&vars).to_string(), "2h5s");
+/// assert_eq!(poll_interval_from(&vars).to_string(), "250ms");
+/// assert!(gen_help().contains("(default: 1m30s)"));
+/// ```
+pub struct Duration<U = Seconds> {
+    duration: std::time::Duration,
+    unit: PhantomData<U>,
+}
+impl<U> Duration<U> {
+    pub fn new(duration: std::time::Duration) -> Self {
+        Self {
+            duration,
+            unit: PhantomData,
+        }
+    }
+    pub fn into_inner(self) -> std::time::Duration {
+        self.duration
+    }
+}
+impl<U: Unit> FromStr for Duration<U> {
+    type Err = ParseDurationError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err(ParseDurationError::Empty);
+        }
+        let nanos = if s.bytes().all(|b| b.is_ascii_digit()) {
+            number(s)?.checked_mul(U::NANOS.into())
+        } else {
+            sum_of_parts(s)?
+        };
+        let nanos = nanos
+            .filter(|&nanos| nanos <= std::time::Duration::MAX.as_nanos())
+            .ok_or(ParseDurationError::TooLarge)?;
+        let secs = (nanos / NANOS_PER_SEC) as u64;
+        let subsec_nanos = (nanos % NANOS_PER_SEC) as u32;
+        Ok(Self::new(std::time::Duration::new(secs, subsec_nanos)))
+    }
+}
+impl<U> fmt::Display for Duration<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut nanos = self.duration.as_nanos();
+        if nanos == 0 {
+            return f.write_str("0s");
+        }
+        for &(suffix, size) in UNITS {
+            let count = nanos / size;
+            if count > 0 {
+                write!(f, "{count}{suffix}")?;
+                nanos %= size;
+            }
+        }
+        Ok(())
+    }
+}
+impl<U> fmt::Debug for Duration<U> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.duration, f)
+    }
+}
+impl<U> Deref for Duration<U> {
+    type Target = std::time::Duration;
+    fn deref(&self) -> &Self::Target {
+        &self.duration
+    }
+}
+impl<U> DerefMut for Duration<U> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.duration
+    }
+}
+impl<U> From<std::time::Duration> for Duration<U> {
+    fn from(duration: std::time::Duration) -> Self {
+        Self::new(duration)
+    }
+}
+impl<U> From<Duration<U>> for std::time::Duration {
+    fn from(duration: Duration<U>) -> Self {
+        duration.duration
+    }
+}
+// These are written by hand, since deriving them would require `U` to implement them too.
+impl<U> Clone for Duration<U> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<U> Copy for Duration<U> {}
+impl<U> Default for Duration<U> {
+    fn default() -> Self {
+        Self::new(std::time::Duration::ZERO)
+    }
+}
+impl<U> PartialEq for Duration<U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.duration == other.duration
+    }
+}
+impl<U> Eq for Duration<U> {}
+impl<U> PartialOrd for Duration<U> {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+impl<U> Ord for Duration<U> {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.duration.cmp(&other.duration)
+    }
+}
+impl<U> Hash for Duration<U> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.duration.hash(state)
+    }
+}
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Every unit, largest first, with the number of nanoseconds in it.
+const UNITS: &[(&str, u128)] = &[
+    ("d", 86_400 * NANOS_PER_SEC),
+    ("h", 3_600 * NANOS_PER_SEC),
+    ("m", 60 * NANOS_PER_SEC),
+    ("s", NANOS_PER_SEC),
+    ("ms", 1_000_000),
+    ("us", 1_000),
+    ("ns", 1),
+];
+
+fn number(digits: &str) -> Result<u128, ParseDurationError> {
+    digits.parse().map_err(|_| ParseDurationError::TooLarge)
+}
+
+/// Add up the parts of a duration like `1h 30m`, in nanoseconds,
+/// or return `None` if the sum overflows.
+fn sum_of_parts(mut s: &str) -> Result<Option<u128>, ParseDurationError> {
+    let mut total = Some(0u128);
+    while !s.is_empty() {
+        let digits = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+        if digits == 0 {
+            return Err(ParseDurationError::MissingNumber);
+        }
+        let count = number(&s[..digits])?;
+        s = s[digits..].trim_start();
+        let letters = s.find(|c: char| !c.is_alphabetic()).unwrap_or(s.len());
+        let size = match &s[..letters] {
+            "" => return Err(ParseDurationError::MissingUnit),
+            "µs" => 1_000,
+            unit => match UNITS.iter().find(|&&(suffix, _)| suffix == unit) {
+                Some(&(_, size)) => size,
+                None => return Err(ParseDurationError::UnknownUnit(unit.to_owned())),
+            },
+        };
+        s = s[letters..].trim_start();
+        total = total
+            .zip(count.checked_mul(size))
+            .and_then(|(total, part)| total.checked_add(part));
+    }
+    Ok(total)
+}
+
+/// The unit a [`Duration`] uses for bare numbers.
+pub trait Unit {
+    /// The number of nanoseconds in one of this unit.
+    const NANOS: u64;
+}
+macro_rules! units {
+    ($($(#[$attr:meta])* $name:ident = $nanos:expr;)*) => {$(
+        $(#[$attr])*
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        pub struct $name;
+        impl Unit for $name {
+            const NANOS: u64 = $nanos;
+        }
+    )*};
+}
+units! {
+    /// Nanoseconds, written `ns`.
+    Nanos = 1;
+    /// Microseconds, written `us` or `µs`.
+    Micros = 1_000;
+    /// Milliseconds, written `ms`.
+    Millis = 1_000_000;
+    /// Seconds, written `s`.
+    Seconds = 1_000_000_000;
+    /// Minutes, written `m`.
+    Minutes = 60 * Seconds::NANOS;
+    /// Hours, written `h`.
+    Hours = 60 * Minutes::NANOS;
+    /// Days, written `d`.
+    Days = 24 * Hours::NANOS;
+}
+
+/// The ways in which parsing a [`Duration`] can fail.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseDurationError {
+    /// The text is empty, or only whitespace.
+    Empty,
+    /// A unit isn't preceded by a number, as with `h30m`.
+    MissingNumber,
+    /// A number in a duration of several parts isn't followed by a unit, as with `1h30`.
+    MissingUnit,
+    /// A unit isn't one of those understood.
+    UnknownUnit(String),
+    /// The duration is larger than a [`std::time::Duration`] can hold.
+    TooLarge,
+}
+impl fmt::Display for ParseDurationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const EXPECTED: &str = "like `30s` or `1h30m`";
+        match self {
+            Self::Empty => write!(f, "expected a duration, {EXPECTED}"),
+            Self::MissingNumber => write!(f, "expected a number before each unit, {EXPECTED}"),
+            Self::MissingUnit => write!(f, "expected a unit after each number, {EXPECTED}"),
+            Self::UnknownUnit(unit) => write!(
+                f,
+                "unknown unit `{unit}`, expected one of ns, us, ms, s, m, h or d"
+            ),
+            Self::TooLarge => f.write_str("the duration is too large"),
+        }
+    }
+}
+impl Error for ParseDurationError {}
@@ -0,0 +1,189 @@
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+
+/// The ways in which reading an env var declared with [`require_envs`](crate::require_envs) can fail.
+///
+/// Its message names the var, and for invalid values, the value and the type it should have been:
+/// ```
+/// use std::collections::HashMap;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     server_port!, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server port";
+/// }
+///
+/// let e = server_port_from(&HashMap::from([("FERRISCRAFT_USERS_PORT", "80a")])).unwrap_err();
+/// assert_eq!(
+///     e.to_string(),
+///     r#"FERRISCRAFT_USERS_PORT="80a" is not a valid u16: invalid digit found in string"#,
+/// );
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum EnvError {
+    /// The var is required, but is not set.
+    Missing {
+        var: &'static str,
+        help: &'static str,
+    },
+    /// The var is set, but its value is not valid unicode.
+    ///
+    /// For vars marked `#[secret]`, `value` is `***`.
+    NotUnicode {
+        var: &'static str,
+        value: OsString,
+        help: &'static str,
+    },
+    /// The var is set, but its value could not be parsed.
+    ///
+    /// If the [`FromStr::Err`](std::str::FromStr::Err) of the declared type doesn't implement [`Error`],
+    /// `source` holds an error whose message is the [`Debug`](fmt::Debug) output of the original.
+    /// For vars marked `#[secret]`, `value` is `***`, and since parse errors may quote the value
+    /// they failed on, `source` holds an error which only says the value is not a valid `type_name`.
+    /// The message leaves both out.
+    Invalid {
+        var: &'static str,
+        value: String,
+        /// The declared type, as written.
+        type_name: &'static str,
+        secret: bool,
+        source: Box<dyn Error + Send + Sync>,
+        help: &'static str,
+    },
+}
+impl EnvError {
+    /// The name of the env var.
+    pub fn var(&self) -> &'static str {
+        match self {
+            Self::Missing { var, .. }
+            | Self::NotUnicode { var, .. }
+            | Self::Invalid { var, .. } => var,
+        }
+    }
+    /// The (trimmed) help text declared for this var.
+    pub fn help(&self) -> &'static str {
+        match self {
+            Self::Missing { help, .. }
+            | Self::NotUnicode { help, .. }
+            | Self::Invalid { help, .. } => help,
+        }
+    }
+}
+impl fmt::Display for EnvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { var, .. } => write!(f, "{var} is not set"),
+            Self::NotUnicode { var, .. } => write!(f, "{var} is not valid unicode"),
+            Self::Invalid {
+                var,
+                secret: true,
+                type_name,
+                ..
+            } => write!(f, "{var} is not a valid {type_name}"),
+            Self::Invalid {
+                var,
+                value,
+                type_name,
+                source,
+                ..
+            } => write!(f, "{var}={value:?} is not a valid {type_name}: {source}"),
+        }
+    }
+}
+impl Error for EnvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Invalid { source, .. } => Some(&**source),
+            _ => None,
+        }
+    }
+}
+
+/// What an [`EnvError::Invalid`] for a secret var holds in place of the error its value failed to parse with.
+#[derive(Debug)]
+pub(crate) struct RedactedError {
+    pub(crate) type_name: &'static str,
+}
+impl fmt::Display for RedactedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "not a valid {}", self.type_name)
+    }
+}
+impl Error for RedactedError {}
+
+/// A single declaration which failed to check out,
+/// as reported by the fallible assert function generated by [`require_envs`](crate::require_envs).
+#[derive(Debug)]
+pub struct Failure {
+    /// The name of the getter function generated for this var,
+    /// or of the field it's read into by [`FromEnv`](crate::FromEnv).
+    pub getter: &'static str,
+    pub error: EnvError,
+}
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+impl Error for Failure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Every [`Failure`] found while checking a list of env vars.
+///
+/// This is never empty when returned by a generated fallible assert function.
+#[derive(Debug, Default)]
+pub struct Errors {
+    failures: Vec<Failure>,
+}
+impl Errors {
+    pub fn len(&self) -> usize {
+        self.failures.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, Failure> {
+        self.failures.iter()
+    }
+}
+impl FromIterator<Failure> for Errors {
+    fn from_iter<I: IntoIterator<Item = Failure>>(iter: I) -> Self {
+        Self {
+            failures: iter.into_iter().collect(),
+        }
+    }
+}
+impl IntoIterator for Errors {
+    type Item = Failure;
+    type IntoIter = std::vec::IntoIter<Failure>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.failures.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a Errors {
+    type Item = &'a Failure;
+    type IntoIter = std::slice::Iter<'a, Failure>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.failures.iter()
+    }
+}
+
+/// Each failure is written on its own line, followed by its help text.
+impl fmt::Display for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, failure) in self.failures.iter().enumerate() {
+            if i != 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{failure}\n    {}", failure.error.help())?;
+        }
+        Ok(())
+    }
+}
+impl Error for Errors {}
@@ -4,12 +4,105 @@
 
 use std::ops::{Deref, DerefMut};
 use std::str::FromStr;
+
+mod byte_size;
+pub mod dotenv;
+pub mod duration;
+mod error;
+mod list;
+mod map;
+mod private;
+pub mod render;
+mod secret;
+mod source;
+mod status;
+mod toggle;
+pub use byte_size::{ByteSize, ParseByteSizeError};
+pub use duration::Duration;
+pub use error::{EnvError, Errors, Failure};
+pub use list::{List, ListError};
+pub use map::{Map, MapError};
+pub use render::{VarInfo, VarKind};
+pub use secret::Secret;
+pub use source::{Or, ProcessEnv, Snapshot, Source};
+pub use status::{Status, VarState, VarStatus};
+pub use toggle::{Toggle, ToggleError};
+
+/// Derive a constructor which reads each field of a struct from an env var.
+///
+/// This generates an inherent `from_env() -> Result<Self, Errors>`,
+/// which reports every missing or malformed var at once, like the `try_assert` function
+/// [`require_envs`] can generate, and an inherent `help() -> String`, which collects the help text
+/// for each var like the help function [`require_envs`] generates does.
+/// It also generates `from_source`, which reads the vars from a given [`Source`] instead.
+///
+/// Each field is annotated with `#[menv(...)]`, which takes:
+/// - `var = "NAME"`, the name of the env var to read (required)
+/// - `help = "..."`, the help text for the var (if not given, the field's doc comment is used)
+/// - `optional`, to leave an `Option<T>` field as `None` when its var is unset,
+///   like the `?` getter modifier
+/// - `default` or `default = value`, to fall back to a default value when the var is unset,
+///   like the `~` getter modifier and `= value` declarations
+/// - `list`, to read a comma-separated list into a `Vec<T>` field, like the `*` getter modifier
+/// - `secret`, to keep the value of the var out of errors, like `#[secret]` declarations
+///
+/// Field types must implement [`FromStr`], just like types in [`require_envs`] declarations.
+/// ```
+/// use menv::{Flag, FromEnv};
+///
+/// #[derive(FromEnv)]
+/// struct Config {
+///     /// MENV_DERIVE_DOC_PORT, if set, overrides the server port
+///     #[menv(var = "MENV_DERIVE_DOC_PORT", default = 8080)]
+///     port: u16,
+///     #[menv(var = "MENV_DERIVE_DOC_PLUGIN_DIR", optional,
+///            help = "MENV_DERIVE_DOC_PLUGIN_DIR, if set, overrides the plugin directory")]
+///     plugin_dir: Option<String>,
+///     /// MENV_DERIVE_DOC_OVERFLOW_CHECKS, if set, makes all additional overflow checks run
+///     #[menv(var = "MENV_DERIVE_DOC_OVERFLOW_CHECKS", default)]
+///     overflow_checks: Flag,
+/// }
+///
+/// let config = Config::from_env().unwrap();
+/// assert_eq!(config.port, 8080);
+/// assert_eq!(config.plugin_dir, None);
+/// assert!(!*config.overflow_checks);
+/// assert!(Config::help().starts_with("MENV_DERIVE_DOC_PORT, if set, overrides the server port (default: 8080)\n"));
+/// ```
+pub use menv_proc_macro::FromEnv;
+
 /// Generate the following:
 /// - A function which asserts the presence and well-formedness of a list of env vars
 /// - A function which returns a `bool` representing whether any of the required vars are set
 /// - A function which returns a `String` representing the collected help messages for the list of vars
 /// - A list of functions, one for each environment variable required, which parse and return the associated env var
 ///
+/// The names of the first three functions are given, in that order, at the start of the invocation.
+/// They may be followed by `key = name` pairs, each of which generates an additional function:
+/// - `try_assert = name` generates a function which checks every env var in the list
+///   (including optional ones, if they are set), and returns an [`Errors`] holding
+///   one [`Failure`] for each which is missing or malformed, instead of panicking at the first
+/// - `status = name` generates a function which returns the [`Status`] of every env var in the list,
+///   telling whether each is set, and if so, whether it is valid, and if not, whether it has a default value.
+///   Its `Display` implementation prints a table meant for people to read
+/// - `snapshot = name` generates a function which returns a [`Snapshot`] of every env var in the list,
+///   taken the first time it's called, and makes every other generated function read from that snapshot
+///   (see [below](#snapshots))
+/// - `help_table = name` generates a function which returns a table of every env var in the list,
+///   with its type, whether it's required, optional or has a default value, and its help text,
+///   aligned for reading in a terminal
+/// - `help_markdown = name` generates a function which returns the same table, formatted in Markdown
+/// - `help_roff = name` generates a function which returns an `ENVIRONMENT` section for a man page,
+///   formatted in roff, which describes every env var in the list the same way
+/// - `json_schema = name` generates a function which returns a JSON Schema describing the environment,
+///   as an object with a string property for each env var in the list, which can be used to validate
+///   configuration before it's deployed
+/// - `vars = NAME` generates a `static` holding a [`VarInfo`] for every env var in the list,
+///   which can be passed to the functions in [`render`], or used to build other tools
+/// - `env_example = name` generates a function which returns the contents of a `.env.example` file,
+///   which lists every env var in the list with its help text as a comment. Vars which aren't required
+///   are commented out, set to their default value when they have a literal one
+///
 /// # Example
 /// Here we fill an `env` module with required environment variables,
 /// print help and exit if none of them are set, runs the asserts for them
@@ -23,9 +116,32 @@ use std::str::FromStr;
 ///
 /// The getter function name can also, instead, be suffixed with `~` to make an env var use
 /// the [`Default`] value of its type when unset. In this example, [`Flag`]'s default value is `false`.
+///
+/// Suffixing it with `*` reads the env var as a comma-separated [`List`] of its type instead,
+/// and makes its getter return a `Vec<T>`, which is empty when the var is unset.
+///
+/// Finally, the getter function name can be suffixed with `!` to make its getter return a
+/// `Result<T, `[`EnvError`]`>` instead of panicking when the var is missing or malformed.
+/// Such a var is still required, so the generated assert function checks it like any other.
+/// In this example, `max_players`'s return type is `Result<u32, EnvError>`.
+///
+/// Instead of a suffix, the getter function name can be followed by `= default` to give the env var
+/// an explicit default value. A literal default is parsed with [`FromStr`] as though the var had been
+/// set to it, while any other expression is used as-is, and must have the declared type.
+/// Either way, the generated help text shows the default next to the var's own help text.
+/// When the declared type is a primitive, like `u16` or `bool`, literal defaults are checked at compile time:
+/// ```compile_fail
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     server_port = 70000, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT, if set, overrides the server port";
+/// }
+/// ```
+/// In this example, `view_distance` returns `12` when unset.
 /// ```
 /// mod env {
-///     use menv::require_envs;
+///     use menv::{require_envs, Flag};
 ///     require_envs! {
 ///         (assert_env_vars, any_set, gen_help);
 ///
@@ -40,6 +156,12 @@ use std::str::FromStr;
 ///
 ///         do_overflow_checks~, "DO_OVERFLOW_CHECKS", Flag,
 ///         "DO_OVERFLOW_CHECKS, if set, makes all additional overflow checks run";
+///
+///         max_players!, "FERRISCRAFT_MAX_PLAYERS", u32,
+///         "FERRISCRAFT_MAX_PLAYERS should be set to the maximum number of players online at once";
+///
+///         view_distance = 12, "FERRISCRAFT_VIEW_DISTANCE", u8,
+///         "FERRISCRAFT_VIEW_DISTANCE, if set, overrides how many chunks away players can see";
 ///     }
 /// }
 /// fn main() {
@@ -51,78 +173,636 @@ use std::str::FromStr;
 ///     }
 /// }
 /// ```
+/// An expression default is shown in the help text as it is written, with line breaks turned into spaces:
+/// ```
+/// const SEA_LEVEL: i32 = 63;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     motd = String::from("Welcome!"), "MENV_DOC_EXPR_MOTD", String,
+///     "MENV_DOC_EXPR_MOTD, if set, overrides the message of the day";
+///
+///     spawn_radius = 16 * 2 + 1, "MENV_DOC_EXPR_SPAWN_RADIUS", u32,
+///     "MENV_DOC_EXPR_SPAWN_RADIUS, if set, overrides the size of the spawn area";
+///
+///     max_players = if cfg!(debug_assertions) { 4 } else {
+///         64
+///     }, "MENV_DOC_EXPR_MAX_PLAYERS", u32,
+///     "MENV_DOC_EXPR_MAX_PLAYERS, if set, overrides the maximum number of players online at once";
+///
+///     void_depth = -SEA_LEVEL, "MENV_DOC_EXPR_VOID_DEPTH", i32,
+///     "MENV_DOC_EXPR_VOID_DEPTH, if set, overrides how far below sea level the void starts";
+/// }
+/// assert!(gen_help().contains("(default: String::from(\"Welcome!\"))"));
+/// assert!(gen_help().contains("(default: 16 * 2 + 1)"));
+/// assert!(gen_help().contains("(default: if cfg!(debug_assertions) { 4 } else { 64 })"));
+/// assert!(gen_help().contains("(default: -SEA_LEVEL)"));
+/// assert_eq!(void_depth(), -63);
+/// ```
+/// Help text is trimmed, whether or not the var has a default, so it can start on its own line:
+/// ```
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     server_port = 25565, "MENV_DOC_TRIM_PORT", u16, "
+///         MENV_DOC_TRIM_PORT, if set, overrides the server port
+///     ";
+///
+///     db_path, "MENV_DOC_TRIM_DB", String, "
+///         MENV_DOC_TRIM_DB should be set to the path to the users database
+///     ";
+/// }
+/// assert_eq!(gen_help(), "\
+/// MENV_DOC_TRIM_PORT, if set, overrides the server port (default: 25565)
+/// MENV_DOC_TRIM_DB should be set to the path to the users database
+/// ");
+/// ```
+///
+/// # Collecting every failure
+/// Asserting with the generated assert function panics at the first var which is missing
+/// or malformed. Naming a `try_assert` function instead reports all of them at once:
+/// ```
+/// mod env {
+///     menv::require_envs! {
+///         (assert_env_vars, any_set, gen_help, try_assert = try_assert_env_vars);
+///
+///         server_port, "MENV_DOC_MISSING_PORT", u16,
+///         "MENV_DOC_MISSING_PORT should be set to the desired server port";
+///
+///         db_path, "MENV_DOC_MISSING_DB", String,
+///         "MENV_DOC_MISSING_DB should be set to the path to the users database";
+///     }
+/// }
+/// let errors = env::try_assert_env_vars().unwrap_err();
+/// let getters: Vec<_> = errors.iter().map(|failure| failure.getter).collect();
+/// assert_eq!(getters, ["server_port", "db_path"]);
+/// assert!(errors.iter().all(|failure| matches!(failure.error, menv::EnvError::Missing { .. })));
+/// ```
+///
+/// # Reading from other sources
+/// Every generated function which reads env vars has a variant with `_from` appended to its name,
+/// which reads them from a given [`Source`] instead of from the process environment,
+/// like `assert_env_vars_from` and `server_port_from`. This lets tests supply their own env vars
+/// without touching the environment of the process they run in:
+/// ```
+/// use std::collections::HashMap;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help, try_assert = try_assert_env_vars);
+///
+///     server_port, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server port";
+///
+///     plugin_dir?, "XLANG_PLUGIN_DIR", String,
+///     "XLANG_PLUGIN_DIR, if set, overrides the directory that lccc looks for xlang plugins";
+/// }
+///
+/// let vars = HashMap::from([("FERRISCRAFT_USERS_PORT", "25565")]);
+/// assert!(any_set_from(&vars));
+/// assert_env_vars_from(&vars);
+/// assert_eq!(server_port_from(&vars), 25565);
+/// assert_eq!(plugin_dir_from(&vars), None);
+///
+/// let empty = HashMap::<String, String>::new();
+/// assert!(!any_set_from(&empty));
+/// assert_eq!(try_assert_env_vars_from(&empty).unwrap_err().len(), 1);
+/// ```
+///
+/// # Caching
+/// Putting `#[cached]` in front of a declaration makes its getter read and parse the var only once,
+/// the first time it succeeds, and return a `&'static` reference to the stored value from then on.
+/// A cached getter for an optional var returns an `Option<&'static T>` instead,
+/// and one declared with `!` returns a `Result<&'static T, EnvError>`.
+/// The generated assert function fills the cache of every cached getter, once the asserts pass.
+/// Every getter in an invocation can be cached by putting `#![cached]` before the first declaration.
+///
+/// The `_from` variants of cached getters are not cached, since they may be given a different source each time.
+/// ```
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     #[cached]
+///     plugin_dirs = "/usr/lib/xlang", "MENV_DOC_CACHED_PLUGIN_DIRS", String,
+///     "MENV_DOC_CACHED_PLUGIN_DIRS, if set, overrides the directories that lccc looks for xlang plugins in";
+/// }
+///
+/// assert_env_vars();
+/// let dirs: &'static String = plugin_dirs();
+/// assert_eq!(dirs, "/usr/lib/xlang");
+/// assert!(std::ptr::eq(dirs, plugin_dirs()));
+/// ```
+///
+/// # Secrets
+/// Putting `#[secret]` in front of a declaration keeps the value of its var out of everything menv prints:
+/// the `value` of its [`EnvError`]s is `***`, the error its value failed to parse with is replaced
+/// by one which doesn't quote it, and its value in the [`Debug`](std::fmt::Debug) output of a [`Snapshot`]
+/// is `***` too. Declarations with a type of [`Secret<T>`], or of any type containing one,
+/// are secret without being marked,
+/// and the getter's return value doesn't print its value either.
+/// ```
+/// use std::collections::HashMap;
+/// use std::str::FromStr;
+///
+/// struct Pin(u32);
+/// impl FromStr for Pin {
+///     type Err = String;
+///     fn from_str(s: &str) -> Result<Self, Self::Err> {
+///         s.parse().map(Pin).map_err(|_| format!("{s:?} is not a number"))
+///     }
+/// }
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help, try_assert = try_assert_env_vars);
+///
+///     #[secret]
+///     db_password, "MENV_DOC_SECRET_DB_PASSWORD", Pin,
+///     "MENV_DOC_SECRET_DB_PASSWORD should be set to the numeric database password";
+/// }
+///
+/// let vars = HashMap::from([("MENV_DOC_SECRET_DB_PASSWORD", "hunter2")]);
+/// let errors = try_assert_env_vars_from(&vars).unwrap_err();
+/// assert!(!format!("{errors} {errors:?}").contains("hunter2"));
+///
+/// // Error reporters like `anyhow`'s print every error in the chain of sources.
+/// let mut error: Option<&dyn std::error::Error> = errors.iter().next().map(|f| f as _);
+/// while let Some(e) = error {
+///     assert!(!format!("{e} {e:?}").contains("hunter2"));
+///     error = e.source();
+/// }
+/// ```
+/// A secret var's default value is kept out of the generated documentation as well.
+/// It is shown as `***` in help text, and left out of `.env.example` files and JSON Schemas:
+/// ```
+/// menv::require_envs! {
+///     (
+///         assert_env_vars, any_set, gen_help,
+///         help_table = gen_help_table,
+///         env_example = gen_env_example,
+///         json_schema = gen_json_schema,
+///     );
+///
+///     #[secret]
+///     db_password = "hunter2", "MENV_DOC_SECRET_DEFAULT_PASSWORD", String,
+///     "MENV_DOC_SECRET_DEFAULT_PASSWORD, if set, overrides the database password";
+/// }
+///
+/// for text in [gen_help(), gen_help_table(), gen_env_example(), gen_json_schema()] {
+///     assert!(!text.contains("hunter2"));
+/// }
+/// assert!(gen_help().contains("(default: ***)"));
+/// ```
+///
+/// # Snapshots
+/// Since getters read the environment every time they're called, a var which is changed while the program
+/// is running will be seen to change by them. To prevent that, `snapshot = name` can be given
+/// after the main three function names, to generate a function which reads every declared var once,
+/// and stores their values in a [`Snapshot`]. Every other generated function, aside from the `_from` variants,
+/// reads from this snapshot instead of from the environment.
+///
+/// The snapshot is taken by whichever generated function is called first, which is normally the assert function.
+/// ```
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help, snapshot = env_snapshot);
+///
+///     server_port, "MENV_DOC_SNAPSHOT_PORT", u16,
+///     "MENV_DOC_SNAPSHOT_PORT should be set to the desired server port";
+/// }
+///
+/// std::env::set_var("MENV_DOC_SNAPSHOT_PORT", "25565");
+/// assert_env_vars();
+/// std::env::set_var("MENV_DOC_SNAPSHOT_PORT", "8080");
+/// assert_eq!(server_port(), 25565);
+/// ```
+///
+/// # Generated documentation
+/// The declarations can also be turned into documentation for users of a program,
+/// besides the help text returned by the help function, like tables, man pages, or templates for `.env` files:
+/// ```
+/// menv::require_envs! {
+///     (
+///         assert_env_vars, any_set, gen_help,
+///         help_table = gen_help_table,
+///         help_markdown = gen_help_markdown,
+///         env_example = gen_env_example,
+///     );
+///
+///     server_port = 25565, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT, if set, overrides the server port";
+///
+///     db_path, "FERRISCRAFT_USERS_DB", String,
+///     "FERRISCRAFT_USERS_DB should be set to the path to the users database";
+/// }
+///
+/// assert_eq!(gen_help_table(), "\
+/// VAR                     TYPE    STATUS          HELP
+/// FERRISCRAFT_USERS_PORT  u16     default: 25565  FERRISCRAFT_USERS_PORT, if set, overrides the server port
+/// FERRISCRAFT_USERS_DB    String  required        FERRISCRAFT_USERS_DB should be set to the path to the users database
+/// ");
+/// assert_eq!(gen_help_markdown(), "\
+/// | Variable | Type | Status | Description |
+/// | --- | --- | --- | --- |
+/// | `FERRISCRAFT_USERS_PORT` | `u16` | default: `25565` | FERRISCRAFT_USERS_PORT, if set, overrides the server port |
+/// | `FERRISCRAFT_USERS_DB` | `String` | required | FERRISCRAFT_USERS_DB should be set to the path to the users database |
+/// ");
+/// assert_eq!(gen_env_example(), "\
+/// ## FERRISCRAFT_USERS_PORT, if set, overrides the server port
+/// ## FERRISCRAFT_USERS_PORT=25565
+///
+/// ## FERRISCRAFT_USERS_DB should be set to the path to the users database
+/// FERRISCRAFT_USERS_DB=
+/// ");
+/// ```
+///
+/// # Lints
+/// Declarations are checked for mistakes which `std::env` can't catch until runtime, if ever.
+/// It is a compile error to declare the same env var or getter name twice,
+/// or to give an env var a name which is empty or contains `=` or NUL, since such a var can never be set:
+/// ```compile_fail
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     server_port, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server port";
+///
+///     server_address, "FERRISCRAFT_USERS_PORT", String,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server address";
+/// }
+/// ```
+/// Likewise, a getter can't share its name, or the name of its `_from` variant,
+/// with another getter or any of the other generated functions:
+/// ```compile_fail
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     server_port, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server port";
+///
+///     server_port_from, "FERRISCRAFT_USERS_PORT_FROM", String,
+///     "FERRISCRAFT_USERS_PORT_FROM should be set to where the server port comes from";
+/// }
+/// ```
+///
+/// Env var names which aren't `UPPER_SNAKE_CASE` are allowed by default, but can be linted against
+/// by putting `#![warn(non_upper_snake_case)]` or `#![deny(non_upper_snake_case)]`
+/// between the function names and the first declaration.
 #[macro_export]
 macro_rules! require_envs {
-    // We set a default visibility which is different from Rust's default to private.
-    (@func $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
-        $crate::require_envs! {@func pub $fname ?, $ename, $ty, $etext}
-    };
-    (@func $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
+    // Declarations reach these arms through the proc macros in `__private`, which parse them,
+    // report any errors, and fill in a default visibility of `pub` when none is given.
+    // Arms for getters also receive the name of the getter's `_from` variant first.
+    (@func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
         #[doc = $crate::__private::trimmed_help!($etext)]
         $vis fn $fname() -> $crate::__private::Option<$ty> {
-            let x = $crate::__private::env::var($ename).ok();
-            x.and_then(|x| {
-                $crate::__private::Option::Some($crate::__private::FromStr::from_str(&x).expect($etext))
-            })
+            $from($source)
         }
     };
-    (@func $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {
-        $crate::require_envs! {@func pub $fname ~, $ename, $ty, $etext}
-    };
-    (@func $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {
+    (@func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident !, $ename:literal, $ty:ty, $etext:literal) => {
         #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> $crate::__private::Result<$ty, $crate::EnvError> {
+            $from($source)
+        }
+    };
+    (@func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident = $dkind:ident $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::require_envs! {@etext $(#[$secret])? $vis $fname = $dkind $default, $ename, $ty, $etext}]
         $vis fn $fname() -> $ty {
-            let x = $crate::__private::env::var($ename).ok();
-            let x = x.and_then(|x| {
-                $crate::__private::Option::Some($crate::__private::FromStr::from_str(&x).expect($etext))
-            });
-            x.unwrap_or_default()
+            $from($source)
         }
     };
-    (@func $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
-        $crate::require_envs! {@func pub $fname, $ename, $ty, $etext}
+    (@func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> $crate::__private::Vec<$ty> {
+            $from($source)
+        }
     };
-    (@func $vis:vis $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
+    (@func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident $(~)?, $ename:literal, $ty:ty, $etext:literal) => {
         #[doc = $crate::__private::trimmed_help!($etext)]
         $vis fn $fname() -> $ty {
-            $crate::__private::FromStr::from_str(&$crate::__private::env::var($ename).expect($etext)).expect($etext)
+            $from($source)
+        }
+    };
+    // Cached getters return references to values which are parsed once, the first time they're read successfully.
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> $crate::__private::Option<&'static $ty> {
+            static CACHE: $crate::__private::OnceLock<$crate::__private::Option<$ty>> = $crate::__private::OnceLock::new();
+            $crate::__private::unwrap($crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $ty, $etext}
+            }))
+            .as_ref()
         }
     };
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> &'static $ty {
+            static CACHE: $crate::__private::OnceLock<$ty> = $crate::__private::OnceLock::new();
+            $crate::__private::unwrap($crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $ty, $etext}
+                    .map($crate::__private::Option::unwrap_or_default)
+            }))
+        }
+    };
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> &'static $crate::__private::Vec<$ty> {
+            static CACHE: $crate::__private::OnceLock<$crate::__private::Vec<$ty>> = $crate::__private::OnceLock::new();
+            $crate::__private::unwrap($crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@list $source, $(#[$secret])? $ename, $ty, $etext}
+            }))
+        }
+    };
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident !, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> $crate::__private::Result<&'static $ty, $crate::EnvError> {
+            static CACHE: $crate::__private::OnceLock<$ty> = $crate::__private::OnceLock::new();
+            $crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@require $source, $(#[$secret])? $ename, $ty, $etext}
+            })
+        }
+    };
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident = $dkind:ident $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::require_envs! {@etext $(#[$secret])? $vis $fname = $dkind $default, $ename, $ty, $etext}]
+        $vis fn $fname() -> &'static $ty {
+            static CACHE: $crate::__private::OnceLock<$ty> = $crate::__private::OnceLock::new();
+            $crate::__private::unwrap($crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $ty, $etext}
+                    .map(|x| x.unwrap_or_else(|| $crate::require_envs! {@default $ename, $ty, $dkind $default}))
+            }))
+        }
+    };
+    (@cached_func [$source:expr] $from:ident $(#[$secret:ident])? $vis:vis $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $fname() -> &'static $ty {
+            static CACHE: $crate::__private::OnceLock<$ty> = $crate::__private::OnceLock::new();
+            $crate::__private::unwrap($crate::__private::cached(&CACHE, || {
+                $crate::require_envs! {@require $source, $(#[$secret])? $ename, $ty, $etext}
+            }))
+        }
+    };
+    // The `_from` variants are never cached, since they may read from a different source each time.
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $crate::__private::Option<$ty> {
+            $crate::__private::unwrap($crate::require_envs! {@lookup source, $(#[$secret])? $ename, $ty, $etext})
+        }
+    };
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $ty {
+            $crate::__private::unwrap($crate::require_envs! {@lookup source, $(#[$secret])? $ename, $ty, $etext}).unwrap_or_default()
+        }
+    };
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $crate::__private::Vec<$ty> {
+            $crate::__private::unwrap($crate::require_envs! {@list source, $(#[$secret])? $ename, $ty, $etext})
+        }
+    };
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident !, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $crate::__private::Result<$ty, $crate::EnvError> {
+            $crate::require_envs! {@require source, $(#[$secret])? $ename, $ty, $etext}
+        }
+    };
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident = $dkind:ident $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::require_envs! {@etext $(#[$secret])? $vis $fname = $dkind $default, $ename, $ty, $etext}]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $ty {
+            $crate::__private::unwrap($crate::require_envs! {@lookup source, $(#[$secret])? $ename, $ty, $etext})
+                .unwrap_or_else(|| $crate::require_envs! {@default $ename, $ty, $dkind $default})
+        }
+    };
+    (@from_func $from:ident $(#[$secret:ident])? $vis:vis $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
+        #[doc = $crate::__private::trimmed_help!($etext)]
+        $vis fn $from(source: &(impl $crate::Source + ?Sized)) -> $ty {
+            $crate::__private::unwrap($crate::require_envs! {@require source, $(#[$secret])? $ename, $ty, $etext})
+        }
+    };
+    // Reads and parses a var from `$source`, producing a `Result<Option<$ty>, EnvError>`.
+    // The values of vars marked `#[secret]` are redacted from the error.
+    (@lookup $source:expr, $(#[$secret:ident])? $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::lookup::<$ty>(
+            $source,
+            $ename,
+            $crate::__private::trimmed_help!($etext),
+            $crate::__private::type_name!($ty),
+            $crate::require_envs! {@is_secret $($secret)?},
+            $crate::require_envs! {@empty_is_unset $ty},
+            |e| {
+                #[allow(unused_imports)]
+                use $crate::__private::{DebugKind as _, ErrorKind as _};
+                (&e).menv_error_kind().boxed(e)
+            },
+        )
+    };
+    // Like `@lookup`, but treats an unset var as an error, producing a `Result<$ty, EnvError>`.
+    (@require $source:expr, $(#[$secret:ident])? $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::require(
+            $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $ty, $etext},
+            $ename,
+            $crate::__private::trimmed_help!($etext),
+        )
+    };
+    // Like `@lookup`, but reads a `List` of `$ty`, producing a `Result<Vec<$ty>, EnvError>` which is empty if the var is unset.
+    (@list $source:expr, $(#[$secret:ident])? $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $crate::List<$ty>, $etext}
+            .map(|x| x.map($crate::List::into_inner).unwrap_or_default())
+    };
+    // Produces the default value of a var declared with `= $default`.
+    // Defaults come with a marker from the proc macros which parse them, saying whether they're
+    // a `literal`, which is parsed like a value of the var would be, or any other `expr`.
+    (@default $ename:literal, $ty:ty, literal $default:expr) => {
+        $crate::__private::parse_default::<$ty>($ename, $crate::__private::default_text!($ty, $default))
+    };
+    (@default $ename:literal, $ty:ty, expr $default:expr) => {
+        $default
+    };
+    // Arms used in the bodies of the `_from` functions receive the name of their `source` parameter first.
     // We do not assert the existence of optional variables.
-    (@assert $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {};
-    (@assert $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {};
-    (@assert $vis:vis $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
+    (@assert $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {};
+    (@assert $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {};
+    (@assert $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {};
+    (@assert $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident = $dkind:ident $default:expr, $ename:literal, $ty:ty, $etext:literal) => {};
+    (@assert $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident $(!)?, $ename:literal, $ty:ty, $etext:literal) => {
+        let _ = $crate::__private::unwrap($crate::require_envs! {@require $source, $(#[$secret])? $ename, $ty, $etext});
+    };
+    (@prime $(#[$secret:ident])? $vis:vis $fname:ident $(?)? $(~)? $(!)? $(*)? $(= $dkind:ident $default:expr)?, $ename:literal, $ty:ty, $etext:literal) => {
         let _ = $fname();
     };
-    (@get_res $vis:vis $fname:ident $(?)? $(~)?, $ename:literal, $ty:ty, $etext:literal) => {
-        $crate::__private::env::var($ename)
+    // Produces the `VarInfo` describing a declaration.
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident ?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info ($crate::VarKind::Optional) $(#[$secret])? $fname, $ename, $ty, $etext}
+    };
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident ~, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info ($crate::VarKind::Default) $(#[$secret])? $fname, $ename, $ty, $etext}
+    };
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info ($crate::VarKind::List) $(#[$secret])? $fname, $ename, $crate::List<$ty>, $etext}
+    };
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident = literal $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info
+            ($crate::VarKind::DefaultValue($crate::__private::default_text!($default)))
+            $(#[$secret])? $fname, $ename, $ty, $etext
+        }
+    };
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident = expr $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info ($crate::VarKind::DefaultExpr($crate::__private::expr_text!($default))) $(#[$secret])? $fname, $ename, $ty, $etext}
+    };
+    (@info $(#[$secret:ident])? $vis:vis $fname:ident $(!)?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::require_envs! {@var_info ($crate::VarKind::Required) $(#[$secret])? $fname, $ename, $ty, $etext}
     };
-    (@etext $vis:vis $fname:ident $(?)? $(~)?, $ename:literal, $ty:ty, $etext:literal) => {
-        $etext
+    (@var_info ($kind:expr) $(#[$secret:ident])? $fname:ident, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::VarInfo {
+            getter: stringify!($fname),
+            name: $ename,
+            type_name: $crate::__private::type_name!($ty),
+            kind: $kind,
+            secret: $crate::require_envs! {@is_secret $($secret)?},
+            help: $crate::__private::trimmed_help!($etext),
+        }
+    };
+    (@is_secret secret) => {
+        true
+    };
+    (@is_secret) => {
+        false
+    };
+    // Whether the var is set, counting an empty value as unset when `@lookup` would.
+    (@get_res $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident $(?)? $(~)? $(!)? $(*)? $(= $dkind:ident $default:expr)?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::is_set($source, $ename, $crate::require_envs! {@empty_is_unset $ty})
+    };
+    (@empty_is_unset $ty:ty) => {
+        {
+            #[allow(unused_imports)]
+            use $crate::__private::{EmptyIsSet as _, EmptyIsUnset as _};
+            (&$crate::__private::EmptyProbe::<$ty>($crate::__private::PhantomData)).menv_empty_is_unset()
+        }
     };
-    (($assert_name:ident, $any_set_name:ident, $help_name:ident); $($stream:tt)*) => {
-        // Note: While I now use a proc macro for dividing the input stream into declarations,
-        // the below comments still accurately describe what that proc macro generates invocations of.
-        pub fn $assert_name () {
-            $crate::__private::assert_var_body! {$crate $($stream)*}
+    (@etext $(#[$secret:ident])? $vis:vis $fname:ident $(?)? $(~)? $(!)? $(*)?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::trimmed_help!($etext)
+    };
+    // The default of a secret var could give its value away.
+    (@etext #[secret] $vis:vis $fname:ident = $dkind:ident $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        concat!($crate::__private::trimmed_help!($etext), " (default: ***)")
+    };
+    (@etext $(#[$secret:ident])? $vis:vis $fname:ident = literal $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        concat!(
+            $crate::__private::trimmed_help!($etext),
+            " (default: ",
+            $crate::__private::default_text!($default),
+            ")",
+        )
+    };
+    (@etext $(#[$secret:ident])? $vis:vis $fname:ident = expr $default:expr, $ename:literal, $ty:ty, $etext:literal) => {
+        concat!($crate::__private::trimmed_help!($etext), " (default: ", $crate::__private::expr_text!($default), ")")
+    };
+    (@check $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident $(!)?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::check(stringify!($fname), $crate::require_envs! {@require $source, $(#[$secret])? $ename, $ty, $etext})
+    };
+    (@check $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident *, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::check(stringify!($fname), $crate::require_envs! {@list $source, $(#[$secret])? $ename, $ty, $etext})
+    };
+    // Optional variables are still checked for well-formedness, if they're set.
+    (@check $source:ident, $(#[$secret:ident])? $vis:vis $fname:ident $(?)? $(~)? $(= $dkind:ident $default:expr)?, $ename:literal, $ty:ty, $etext:literal) => {
+        $crate::__private::check(stringify!($fname), $crate::require_envs! {@lookup $source, $(#[$secret])? $ename, $ty, $etext})
+    };
+    // Each of the following generates a function which reads from `$source`,
+    // which is either the process environment or its snapshot, and its `_from` variant, whose name is given second.
+    (@assert_fn [$source:expr] $name:ident $from:ident; $($stream:tt)*) => {
+        pub fn $name() {
+            $from($source);
+            $crate::__private::prime_body! {$crate $($stream)*}
+        }
+        pub fn $from(source: &(impl $crate::Source + ?Sized)) {
+            // Note: While I now use a proc macro for dividing the input stream into declarations,
+            // the below comment still accurately describes what that proc macro generates invocations of.
+            $crate::__private::assert_var_body! {$crate source $($stream)*}
             // $(
-            //     $crate::require_envs! {@assert $a $b $c $d $e $f $g $($h)?}
+            //     $crate::require_envs! {@assert source, $a $b $c $d $e $f $g $($h)?}
             // )*
         }
-        pub fn $any_set_name() -> bool {
-            $crate::__private::any_set_body! {$crate $($stream)*}.iter().any(|x| x.is_ok())
-            // [$($crate::require_envs! {@get_res $a $b $c $d $e $f $g $($h)?}),*].iter().any(|x| x.is_ok())
+    };
+    (@any_set_fn [$source:expr] $name:ident $from:ident; $($stream:tt)*) => {
+        pub fn $name() -> bool {
+            $from($source)
+        }
+        pub fn $from(source: &(impl $crate::Source + ?Sized)) -> bool {
+            $crate::__private::any_set_body! {$crate source $($stream)*}.iter().any(|&set| set)
+            // [$($crate::require_envs! {@get_res source, $a $b $c $d $e $f $g $($h)?}),*].iter().any(|&set| set)
         }
-        pub fn $help_name() -> $crate::__private::String {
-            $crate::__private::help_body!{$crate $($stream)*}.iter().fold($crate::__private::String::new(), |a, x| {
-                a + x + "\n"
+    };
+    (@try_assert_fn [$source:expr] $name:ident $from:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::Result<(), $crate::Errors> {
+            $from($source)
+        }
+        pub fn $from(source: &(impl $crate::Source + ?Sized)) -> $crate::__private::Result<(), $crate::Errors> {
+            $crate::__private::collect_failures($crate::__private::check_body! {$crate source $($stream)*})
+        }
+    };
+    (@vars_static $name:ident; $($stream:tt)*) => {
+        pub static $name: &[$crate::VarInfo] = &$crate::__private::info_body! {$crate $($stream)*};
+    };
+    (@help_table_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::String {
+            $crate::__private::help_table(&$crate::__private::info_body! {$crate $($stream)*})
+        }
+    };
+    (@help_markdown_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::String {
+            $crate::__private::markdown(&$crate::__private::info_body! {$crate $($stream)*})
+        }
+    };
+    (@help_roff_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::String {
+            $crate::__private::roff(&$crate::__private::info_body! {$crate $($stream)*})
+        }
+    };
+    (@json_schema_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::String {
+            $crate::__private::json_schema(&$crate::__private::info_body! {$crate $($stream)*})
+        }
+    };
+    (@env_example_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::__private::String {
+            $crate::__private::env_example(&$crate::__private::info_body! {$crate $($stream)*})
+        }
+    };
+    (@status_fn [$source:expr] $name:ident $from:ident; $($stream:tt)*) => {
+        pub fn $name() -> $crate::Status {
+            $from($source)
+        }
+        pub fn $from(source: &(impl $crate::Source + ?Sized)) -> $crate::Status {
+            $crate::__private::status_body! {$crate source $($stream)*}.into_iter().collect()
+        }
+    };
+    (@snapshot_fn $name:ident; $($stream:tt)*) => {
+        pub fn $name() -> &'static $crate::Snapshot {
+            static SNAPSHOT: $crate::__private::OnceLock<$crate::Snapshot> = $crate::__private::OnceLock::new();
+            SNAPSHOT.get_or_init(|| {
+                $crate::Snapshot::capture(&$crate::__private::var_names! {$crate $($stream)*})
+                    .redacting(&$crate::__private::secret_names! {$crate $($stream)*})
             })
+        }
+    };
+    (($assert_name:ident, $any_set_name:ident, $help_name:ident $(, $extra:ident = $extra_name:ident)* $(,)?); $($stream:tt)*) => {
+        // The proc macros are also told the names of the functions generated here,
+        // so they can reject getters which would clash with them.
+        $crate::require_envs! {@invocation
+            ($assert_name, $any_set_name, $help_name $(, $extra = $extra_name)*)
+            [assert = $assert_name, any_set = $any_set_name, help = $help_name $(, $extra = $extra_name)*]
+            $($stream)*
+        }
+    };
+    (@invocation ($assert_name:ident, $any_set_name:ident, $help_name:ident $(, $extra:ident = $extra_name:ident)*) $($stream:tt)*) => {
+        pub fn $help_name() -> $crate::__private::String {
+            $crate::__private::join_help(&$crate::__private::help_body!{$crate $($stream)*})
             // $crate::__private::String::new() $(+ $crate::require_envs! {@etext $a $b $c $d $e $f $g $($h)?} + "\n")*
         }
-        $crate::__private::getters! {$crate $($stream)*}
+        $crate::__private::getters! {$crate ($($extra = $extra_name),*) $($stream)*}
         // $(
-        //     $crate::require_envs! {@func $a $b $c $d $e $f $g $($h)?}
+        //     $crate::require_envs! {@func [&$crate::ProcessEnv] $a_from $a $b $c $d $e $f $g $($h)?}
+        //     $crate::require_envs! {@from_func $a_from $a $b $c $d $e $f $g $($h)?}
         // )*
+        $crate::__private::extras! {$crate (assert = $assert_name, any_set = $any_set_name) ($($extra = $extra_name),*) $($stream)*}
         $crate::__private::errors! {$crate $($stream)*}
     }
 }
@@ -132,6 +812,7 @@ macro_rules! require_envs {
 ///
 /// This is best used with the `~` getter modifier,
 /// which causes an unset var to use its type's [`Default`] implementation.
+/// To read a var which may also be set to false, use [`Toggle`] instead.
 #[derive(Default, Copy, Clone, Hash, Debug)]
 pub struct Flag {
     pub val: bool,
@@ -155,12 +836,23 @@ impl DerefMut for Flag {
 }
 
 /// This module holds private re-exports which are used by [`require_envs`]
-/// to ensure it always refers to the right external items.
+/// to ensure it always refers to the right external items,
+/// including the runtime helpers its expansion calls, which live in `src/private.rs`.
 #[doc(hidden)]
 pub mod __private {
-    pub use ::menv_proc_macro::{any_set_body, assert_var_body, errors, getters, help_body, trimmed_help};
-    pub use ::std::env;
+    pub use ::menv_proc_macro::{
+        any_set_body, assert_var_body, check_body, default_text, errors, expr_text, extras,
+        getters, help_body, info_body, prime_body, secret_names, status_body, trimmed_help,
+        type_name, var_names,
+    };
+    pub use ::std::marker::PhantomData;
     pub use ::std::option::Option;
+    pub use ::std::result::Result;
     pub use ::std::str::FromStr;
     pub use ::std::string::String;
+    pub use ::std::sync::OnceLock;
+    pub use ::std::vec::Vec;
+
+    pub use crate::private::*;
+    pub use crate::render::{env_example, help_table, json_schema, markdown, roff};
 }
@@ -0,0 +1,100 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A list of values separated by `SEP`, which is a comma by default.
+///
+/// Whitespace around each element is trimmed, and a value which is empty,
+/// or only whitespace, is an empty list.
+/// Declaring a var with the `*` getter modifier reads it as a `List<T>`,
+/// and makes its getter return the `Vec<T>` inside, which is empty when the var is unset.
+/// ```
+/// use std::collections::HashMap;
+/// use menv::List;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     allowed_origins*, "MENV_DOC_LIST_ORIGINS", String,
+///     "MENV_DOC_LIST_ORIGINS, if set, lists the origins allowed to make requests";
+///
+///     worker_ports!, "MENV_DOC_LIST_PORTS", List<u16, ':'>,
+///     "MENV_DOC_LIST_PORTS should be set to the ports of the workers, separated by `:`";
+/// }
+///
+/// let vars = HashMap::from([
+///     ("MENV_DOC_LIST_ORIGINS", "a.com, b.com"),
+///     ("MENV_DOC_LIST_PORTS", "8001:8002:80a3"),
+/// ]);
+/// assert_eq!(allowed_origins_from(&vars), ["a.com", "b.com"]);
+/// assert_eq!(allowed_origins_from(&HashMap::<String, String>::new()), Vec::<String>::new());
+/// assert_eq!(
+///     worker_ports_from(&vars).unwrap_err().to_string(),
+///     r#"MENV_DOC_LIST_PORTS="8001:8002:80a3" is not a valid List<u16, ':'>: element 2 is not valid: invalid digit found in string"#,
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct List<T, const SEP: char = ','>(pub Vec<T>);
+impl<T, const SEP: char> List<T, SEP> {
+    pub fn into_inner(self) -> Vec<T> {
+        self.0
+    }
+}
+impl<T: FromStr, const SEP: char> FromStr for List<T, SEP> {
+    type Err = ListError<T::Err>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Ok(Self(Vec::new()));
+        }
+        s.split(SEP)
+            .enumerate()
+            .map(|(index, element)| {
+                element
+                    .trim()
+                    .parse()
+                    .map_err(|source| ListError { index, source })
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+}
+impl<T, const SEP: char> Deref for List<T, SEP> {
+    type Target = Vec<T>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T, const SEP: char> DerefMut for List<T, SEP> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl<T, const SEP: char> IntoIterator for List<T, SEP> {
+    type Item = T;
+    type IntoIter = std::vec::IntoIter<T>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The error returned when an element of a [`List`] fails to parse.
+///
+/// Its message doesn't include the element itself, so that it doesn't reveal the values of secret vars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ListError<E> {
+    /// The index of the element, counting from 0.
+    pub index: usize,
+    /// The error returned by the element type's [`FromStr`] implementation.
+    pub source: E,
+}
+impl<E: fmt::Display> fmt::Display for ListError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "element {} is not valid: {}", self.index, self.source)
+    }
+}
+impl<E: Error + 'static> Error for ListError<E> {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
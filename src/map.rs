@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// A map written as `key=value` pairs separated by commas, like `team=infra,tier=gold`.
+///
+/// The separator between entries is `ENTRY`, and the one between a key and its value is `PAIR`.
+/// A backslash makes the character after it part of the key or value, even if it's a separator,
+/// a backslash, or whitespace, which is otherwise trimmed from around keys and values.
+/// A value which is empty, or only whitespace, is an empty map.
+/// Keys may only appear once.
+/// ```
+/// use std::collections::HashMap;
+/// use menv::Map;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     labels~, "MENV_DOC_MAP_LABELS", Map<String, String>,
+///     "MENV_DOC_MAP_LABELS, if set, holds labels to attach to metrics, like `team=infra,tier=gold`";
+///
+///     weights!, "MENV_DOC_MAP_WEIGHTS", Map<String, u8, ';', ':'>,
+///     "MENV_DOC_MAP_WEIGHTS should be set to the weight of each backend, like `a:1;b:2`";
+/// }
+///
+/// let vars = HashMap::from([
+///     ("MENV_DOC_MAP_LABELS", r"team = infra, note=a\,b\=c"),
+///     ("MENV_DOC_MAP_WEIGHTS", "a:1; b:2; a:3"),
+/// ]);
+/// let labels = labels_from(&vars);
+/// assert_eq!(labels["team"], "infra");
+/// assert_eq!(labels["note"], "a,b=c");
+/// assert_eq!(
+///     weights_from(&vars).unwrap_err().to_string(),
+///     r#"MENV_DOC_MAP_WEIGHTS="a:1; b:2; a:3" is not a valid Map<String, u8, ';', ':'>: entry 2 repeats the key of entry 0"#,
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Map<K, V, const ENTRY: char = ',', const PAIR: char = '='>(pub HashMap<K, V>);
+impl<K, V, const ENTRY: char, const PAIR: char> Map<K, V, ENTRY, PAIR> {
+    pub fn into_inner(self) -> HashMap<K, V> {
+        self.0
+    }
+}
+impl<K: Hash + Eq, V: PartialEq, const ENTRY: char, const PAIR: char> PartialEq
+    for Map<K, V, ENTRY, PAIR>
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+impl<K: Hash + Eq, V: Eq, const ENTRY: char, const PAIR: char> Eq for Map<K, V, ENTRY, PAIR> {}
+impl<K, V, const ENTRY: char, const PAIR: char> Default for Map<K, V, ENTRY, PAIR> {
+    fn default() -> Self {
+        Self(HashMap::new())
+    }
+}
+impl<K, V, const ENTRY: char, const PAIR: char> FromStr for Map<K, V, ENTRY, PAIR>
+where
+    K: FromStr + Hash + Eq,
+    V: FromStr,
+{
+    type Err = MapError<K::Err, V::Err>;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Each value is stored with the index of its entry, to point at it when its key is repeated.
+        let mut map = HashMap::<K, (usize, V)>::new();
+        for (index, entry) in split_entries(s, ENTRY)?.into_iter().enumerate() {
+            let Some(pair) = entry.iter().position(|&(c, escaped)| c == PAIR && !escaped) else {
+                return Err(match unescape(&entry).is_empty() {
+                    true => MapError::EmptyEntry { index },
+                    false => MapError::MissingSeparator {
+                        index,
+                        separator: PAIR,
+                    },
+                });
+            };
+            let key: K = unescape(&entry[..pair])
+                .parse()
+                .map_err(|source| MapError::Key { index, source })?;
+            let value: V = unescape(&entry[pair + 1..])
+                .parse()
+                .map_err(|source| MapError::Value { index, source })?;
+            if let Some(&(first, _)) = map.get(&key) {
+                return Err(MapError::DuplicateKey { index, first });
+            }
+            map.insert(key, (index, value));
+        }
+        Ok(Self(
+            map.into_iter()
+                .map(|(key, (_, value))| (key, value))
+                .collect(),
+        ))
+    }
+}
+impl<K, V, const ENTRY: char, const PAIR: char> Deref for Map<K, V, ENTRY, PAIR> {
+    type Target = HashMap<K, V>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<K, V, const ENTRY: char, const PAIR: char> DerefMut for Map<K, V, ENTRY, PAIR> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+impl<K, V, const ENTRY: char, const PAIR: char> IntoIterator for Map<K, V, ENTRY, PAIR> {
+    type Item = (K, V);
+    type IntoIter = std::collections::hash_map::IntoIter<K, V>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// Text with each of its characters marked by whether it was escaped.
+type Escaped = Vec<(char, bool)>;
+
+/// Split `s` on unescaped `separator`s, marking which characters were escaped.
+fn split_entries<KE, VE>(s: &str, separator: char) -> Result<Vec<Escaped>, MapError<KE, VE>> {
+    if s.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut entries = vec![Vec::new()];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                let escaped = chars.next().ok_or(MapError::TrailingEscape)?;
+                entries.last_mut().unwrap().push((escaped, true));
+            }
+            c if c == separator => entries.push(Vec::new()),
+            c => entries.last_mut().unwrap().push((c, false)),
+        }
+    }
+    Ok(entries)
+}
+
+/// The text of a key or value, without the whitespace around it.
+fn unescape(chars: &[(char, bool)]) -> String {
+    let blank = |&(c, escaped): &(char, bool)| c.is_whitespace() && !escaped;
+    let start = chars.iter().position(|c| !blank(c)).unwrap_or(chars.len());
+    let end = chars
+        .iter()
+        .rposition(|c| !blank(c))
+        .map_or(start, |end| end + 1);
+    chars[start..end].iter().map(|&(c, _)| c).collect()
+}
+
+/// The ways in which parsing a [`Map`] can fail.
+///
+/// Entries are counted from 0. Messages don't include keys or values,
+/// so that they don't reveal the values of secret vars.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum MapError<KE, VE> {
+    /// An entry is empty, as with `a=1,,b=2`.
+    EmptyEntry { index: usize },
+    /// An entry has no separator between its key and value.
+    MissingSeparator { index: usize, separator: char },
+    /// The key of an entry could not be parsed.
+    Key { index: usize, source: KE },
+    /// The value of an entry could not be parsed.
+    Value { index: usize, source: VE },
+    /// An entry has the same key as an earlier one.
+    DuplicateKey { index: usize, first: usize },
+    /// The text ends with a backslash, which has nothing to escape.
+    TrailingEscape,
+}
+impl<KE: fmt::Display, VE: fmt::Display> fmt::Display for MapError<KE, VE> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyEntry { index } => write!(f, "entry {index} is empty"),
+            Self::MissingSeparator { index, separator } => {
+                write!(
+                    f,
+                    "entry {index} has no `{separator}` between its key and value"
+                )
+            }
+            Self::Key { index, source } => {
+                write!(f, "the key of entry {index} is not valid: {source}")
+            }
+            Self::Value { index, source } => {
+                write!(f, "the value of entry {index} is not valid: {source}")
+            }
+            Self::DuplicateKey { index, first } => {
+                write!(f, "entry {index} repeats the key of entry {first}")
+            }
+            Self::TrailingEscape => f.write_str("the last `\\` has nothing to escape"),
+        }
+    }
+}
+impl<KE, VE> Error for MapError<KE, VE>
+where
+    KE: Error + 'static,
+    VE: Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Key { source, .. } => Some(source),
+            Self::Value { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,185 @@
+//! The runtime side of the code generated by [`require_envs`](crate::require_envs),
+//! re-exported from [`__private`](crate::__private) so the macro can reach it.
+
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::marker::PhantomData;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use crate::error::RedactedError;
+use crate::secret::REDACTED;
+use crate::{EnvError, Errors, Failure, Source, Toggle, VarInfo, VarStatus};
+
+/// Read and parse a single env var from `source`, yielding `None` if it is unset.
+///
+/// `boxed` is supplied by [`require_envs`](crate::require_envs), which knows the concrete
+/// error type and can therefore pick between [`ErrorKind`] and [`DebugKind`].
+pub fn lookup<T: FromStr>(
+    source: &(impl Source + ?Sized),
+    var: &'static str,
+    help: &'static str,
+    type_name: &'static str,
+    secret: bool,
+    empty_is_unset: bool,
+    boxed: impl FnOnce(T::Err) -> Box<dyn Error + Send + Sync>,
+) -> Result<Option<T>, EnvError> {
+    match source.var(var) {
+        Ok(value) if empty_is_unset && value.is_empty() => Ok(None),
+        Ok(value) => match T::from_str(&value) {
+            Ok(x) => Ok(Some(x)),
+            Err(e) => Err(EnvError::Invalid {
+                var,
+                value: if secret { REDACTED.to_owned() } else { value },
+                type_name,
+                secret,
+                source: if secret {
+                    Box::new(RedactedError { type_name })
+                } else {
+                    boxed(e)
+                },
+                help,
+            }),
+        },
+        Err(env::VarError::NotPresent) => Ok(None),
+        Err(env::VarError::NotUnicode(value)) => Err(EnvError::NotUnicode {
+            var,
+            value: if secret { REDACTED.into() } else { value },
+            help,
+        }),
+    }
+}
+
+/// Whether `var` is set in `source`, which it isn't if it's empty and `empty_is_unset` is true,
+/// the same as for [`lookup`].
+pub fn is_set(source: &(impl Source + ?Sized), var: &'static str, empty_is_unset: bool) -> bool {
+    match source.var(var) {
+        Ok(value) => !(empty_is_unset && value.is_empty()),
+        Err(_) => false,
+    }
+}
+
+pub fn require<T>(
+    x: Result<Option<T>, EnvError>,
+    var: &'static str,
+    help: &'static str,
+) -> Result<T, EnvError> {
+    x?.ok_or(EnvError::Missing { var, help })
+}
+
+/// Panic with a message holding both the error and its help text.
+#[track_caller]
+pub fn unwrap<T>(x: Result<T, EnvError>) -> T {
+    match x {
+        Ok(x) => x,
+        Err(e) => panic!("{e}\n{}", e.help()),
+    }
+}
+
+/// Parse the text of a literal default value, as generated by `default_text!`.
+#[track_caller]
+pub fn parse_default<T: FromStr>(var: &'static str, text: &'static str) -> T
+where
+    T::Err: fmt::Debug,
+{
+    match T::from_str(text) {
+        Ok(x) => x,
+        Err(e) => panic!("the default value of {var} ({text:?}) could not be parsed: {e:?}"),
+    }
+}
+
+/// Get the value in `cache`, or fill it with the value produced by `init` if that succeeds.
+pub fn cached<T>(
+    cache: &'static OnceLock<T>,
+    init: impl FnOnce() -> Result<T, EnvError>,
+) -> Result<&'static T, EnvError> {
+    if let Some(x) = cache.get() {
+        return Ok(x);
+    }
+    let x = init()?;
+    Ok(cache.get_or_init(|| x))
+}
+
+pub fn var_status<T>(info: VarInfo, x: Result<Option<T>, EnvError>) -> VarStatus {
+    VarStatus::new(info, x)
+}
+
+pub fn check<T>(getter: &'static str, x: Result<T, EnvError>) -> Result<(), Failure> {
+    x.map(drop).map_err(|error| Failure { getter, error })
+}
+
+// Parse errors are only guaranteed to implement `Debug`, since that's all `expect` ever needed.
+// We use autoref specialization to box them as-is when they are real errors,
+// and fall back to boxing their `Debug` output otherwise.
+pub struct ErrorTag;
+pub struct DebugTag;
+pub trait ErrorKind {
+    fn menv_error_kind(&self) -> ErrorTag {
+        ErrorTag
+    }
+}
+impl<E: Error + Send + Sync + 'static> ErrorKind for E {}
+pub trait DebugKind {
+    fn menv_error_kind(&self) -> DebugTag {
+        DebugTag
+    }
+}
+impl<E: fmt::Debug> DebugKind for &E {}
+impl ErrorTag {
+    pub fn boxed<E: Error + Send + Sync + 'static>(self, e: E) -> Box<dyn Error + Send + Sync> {
+        Box::new(e)
+    }
+}
+impl DebugTag {
+    pub fn boxed<E: fmt::Debug>(self, e: E) -> Box<dyn Error + Send + Sync> {
+        Box::new(DebugError(format!("{e:?}")))
+    }
+}
+#[derive(Debug)]
+struct DebugError(String);
+impl fmt::Display for DebugError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+impl Error for DebugError {}
+
+// `Toggle`s treat an empty value as unset, so we use autoref specialization again
+// to find out whether a declared type is one.
+pub struct EmptyProbe<T>(pub PhantomData<T>);
+pub trait EmptyIsUnset {
+    fn menv_empty_is_unset(&self) -> bool {
+        true
+    }
+}
+impl EmptyIsUnset for EmptyProbe<Toggle> {}
+pub trait EmptyIsSet {
+    fn menv_empty_is_unset(&self) -> bool {
+        false
+    }
+}
+impl<T> EmptyIsSet for &EmptyProbe<T> {}
+
+/// Move the error out of `x`, if there is one, into `failures`.
+pub fn collect<T>(
+    failures: &mut Vec<Failure>,
+    getter: &'static str,
+    x: Result<T, EnvError>,
+) -> Option<T> {
+    x.map_err(|error| failures.push(Failure { getter, error }))
+        .ok()
+}
+
+pub fn join_help(help: &[&str]) -> String {
+    help.iter().fold(String::new(), |a, x| a + x + "\n")
+}
+
+pub fn collect_failures<const N: usize>(checks: [Result<(), Failure>; N]) -> Result<(), Errors> {
+    let errors: Errors = checks.into_iter().filter_map(Result::err).collect();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
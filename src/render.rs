@@ -0,0 +1,378 @@
+//! Renderers for documentation about the env vars in a [`require_envs`](crate::require_envs) invocation.
+//!
+//! These are what the functions generated by `help_table = name` and the like call,
+//! and can be given the `static` generated by `vars = NAME` directly:
+//! ```
+//! menv::require_envs! {
+//!     (assert_env_vars, any_set, gen_help, vars = VARS);
+//!
+//!     server_port?, "FERRISCRAFT_USERS_PORT", u16,
+//!     "FERRISCRAFT_USERS_PORT, if set, overrides the server port";
+//! }
+//!
+//! assert_eq!(VARS[0].name, "FERRISCRAFT_USERS_PORT");
+//! assert_eq!(VARS[0].kind, menv::VarKind::Optional);
+//! assert!(menv::render::markdown(VARS).contains("| `FERRISCRAFT_USERS_PORT` | `u16` | optional |"));
+//! assert!(menv::render::json_schema(VARS).contains(r#""required": []"#));
+//! ```
+
+use crate::dotenv;
+use crate::secret::REDACTED;
+
+/// A description of a single declaration in a [`require_envs`](crate::require_envs) invocation.
+#[derive(Clone, Copy, Debug)]
+pub struct VarInfo {
+    /// The name of the generated getter function.
+    pub getter: &'static str,
+    /// The name of the env var.
+    pub name: &'static str,
+    /// The declared type, as written.
+    pub type_name: &'static str,
+    /// What happens when the var is unset.
+    pub kind: VarKind,
+    /// Whether the var is marked `#[secret]`, so its value shouldn't be shown.
+    pub secret: bool,
+    /// The (trimmed) help text.
+    pub help: &'static str,
+}
+
+impl VarInfo {
+    /// A short description of its kind, like [`VarKind::describe`], but without a secret var's default.
+    pub(crate) fn describe(&self) -> String {
+        match self.kind {
+            VarKind::DefaultValue(_) | VarKind::DefaultExpr(_) if self.secret => {
+                format!("default: {REDACTED}")
+            }
+            kind => kind.describe(),
+        }
+    }
+}
+
+/// What happens when a var is unset, as determined by its getter modifier.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum VarKind {
+    /// The var is required, with or without `!`.
+    Required,
+    /// `?`
+    Optional,
+    /// `~`, which uses the type's [`Default`] value.
+    Default,
+    /// `= value` with a literal value, holding the text it is parsed from.
+    DefaultValue(&'static str),
+    /// `= value` with any other expression, holding its source code.
+    DefaultExpr(&'static str),
+    /// `*`, which reads a list and gives an empty one.
+    List,
+}
+
+impl VarKind {
+    /// A short description, like `required` or `default: 8080`.
+    pub(crate) fn describe(self) -> String {
+        match self {
+            Self::Required => "required".to_owned(),
+            Self::Optional => "optional".to_owned(),
+            Self::Default => "default".to_owned(),
+            Self::DefaultValue(value) | Self::DefaultExpr(value) => format!("default: {value}"),
+            Self::List => "default: empty list".to_owned(),
+        }
+    }
+}
+
+/// Render a table with a row for each var, holding its name, type, kind and help text.
+///
+/// Columns are aligned with spaces, and each line of a multi-line help text starts in the help column.
+pub fn help_table(vars: &[VarInfo]) -> String {
+    let rows = vars.iter().map(|var| {
+        [
+            var.name.to_owned(),
+            var.type_name.to_owned(),
+            var.describe(),
+            var.help.to_owned(),
+        ]
+    });
+    aligned_table(["VAR", "TYPE", "STATUS", "HELP"], rows)
+}
+
+/// Lay out a table with its columns aligned using spaces.
+///
+/// The last column isn't padded, and each line of a multi-line cell in it starts in that column.
+pub(crate) fn aligned_table<const N: usize>(
+    header: [&str; N],
+    rows: impl IntoIterator<Item = [String; N]>,
+) -> String {
+    let rows: Vec<[String; N]> = std::iter::once(header.map(str::to_owned))
+        .chain(rows)
+        .collect();
+    let mut widths = [0; N];
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(&row[..N - 1]) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let indent = widths[..N - 1].iter().map(|width| width + 2).sum::<usize>();
+
+    let mut out = String::new();
+    for row in &rows {
+        // Indentation from the source code would throw off the alignment.
+        let mut last = row[N - 1].lines().map(str::trim);
+        let mut text = String::new();
+        for (cell, width) in row[..N - 1].iter().zip(widths) {
+            text += &format!("{cell:width$}  ");
+        }
+        text += last.next().unwrap_or("");
+        out += text.trim_end();
+        out.push('\n');
+        for rest in last {
+            out += format!("{:indent$}{rest}", "").trim_end();
+            out.push('\n');
+        }
+    }
+    out
+}
+
+/// Render the contents of a `.env.example` file.
+///
+/// Like the other renderers, this trims the indentation a multi-line help text has in the source code:
+/// ```
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help, vars = VARS);
+///
+///     motd?, "MENV_DOC_RENDER_MOTD", String,
+///     "MENV_DOC_RENDER_MOTD, if set, is shown to players when they join.
+///     It may span multiple lines.";
+/// }
+///
+/// assert_eq!(menv::render::env_example(VARS), "\
+/// ## MENV_DOC_RENDER_MOTD, if set, is shown to players when they join.
+/// ## It may span multiple lines.
+/// ## MENV_DOC_RENDER_MOTD=
+/// ");
+/// assert!(menv::render::json_schema(VARS).contains(
+///     r#""description": "MENV_DOC_RENDER_MOTD, if set, is shown to players when they join.\nIt may span multiple lines.""#
+/// ));
+/// ```
+pub fn env_example(vars: &[VarInfo]) -> String {
+    let mut out = String::new();
+    for (i, var) in vars.iter().enumerate() {
+        if i != 0 {
+            out.push('\n');
+        }
+        for line in var.help.lines().map(str::trim) {
+            out += if line.is_empty() { "#" } else { "# " };
+            out += line;
+            out.push('\n');
+        }
+        let (prefix, value) = match var.kind {
+            VarKind::Required => ("", String::new()),
+            VarKind::DefaultValue(value) if !var.secret => ("# ", dotenv::quote(value)),
+            _ => ("# ", String::new()),
+        };
+        out += &format!("{prefix}{}={value}\n", var.name);
+    }
+    out
+}
+
+/// Render a Markdown table with a row for each var, holding its name, type, kind and help text.
+pub fn markdown(vars: &[VarInfo]) -> String {
+    fn escape(text: &str) -> String {
+        text.replace('|', "\\|")
+    }
+    let mut out =
+        String::from("| Variable | Type | Status | Description |\n| --- | --- | --- | --- |\n");
+    for var in vars {
+        let status = match var.kind {
+            VarKind::DefaultValue(value) | VarKind::DefaultExpr(value) => {
+                let value = if var.secret { REDACTED } else { value };
+                format!("default: `{}`", escape(value))
+            }
+            kind => kind.describe(),
+        };
+        let help = var
+            .help
+            .lines()
+            .map(|line| escape(line.trim()))
+            .collect::<Vec<_>>()
+            .join("<br>");
+        out += &format!(
+            "| `{}` | `{}` | {status} | {help} |\n",
+            var.name,
+            escape(var.type_name),
+        );
+    }
+    out
+}
+
+/// Render an `ENVIRONMENT` section for a man page, with a tagged paragraph for each var,
+/// holding its name, type, kind and help text.
+pub fn roff(vars: &[VarInfo]) -> String {
+    fn escape(text: &str) -> String {
+        let text = text.replace('\\', "\\e").replace('-', "\\-");
+        // Lines starting with these would be read as requests.
+        if text.starts_with(['.', '\'']) {
+            format!("\\&{text}")
+        } else {
+            text
+        }
+    }
+    let mut out = String::from(".SH ENVIRONMENT\n");
+    for var in vars {
+        out += &format!(
+            ".TP\n\\fB{}\\fR (\\fI{}\\fR, {})\n",
+            escape(var.name),
+            escape(var.type_name),
+            escape(&var.describe()),
+        );
+        for line in var.help.lines().map(str::trim) {
+            if line.is_empty() {
+                out += ".IP\n";
+            } else {
+                out += &escape(line);
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+/// Render a JSON Schema describing an object with a string property for each var.
+///
+/// Vars which are required are listed as required properties. Values of common types are constrained to
+/// what their `FromStr` implementations accept, like integers or `true` and `false`, and literal default
+/// values are given as `default`s, unless the var is secret.
+pub fn json_schema(vars: &[VarInfo]) -> String {
+    let mut out = String::from(
+        "{\n  \"$schema\": \"https://json-schema.org/draft/2020-12/schema\",\n  \"type\": \"object\",\n  \"properties\": {",
+    );
+    for (i, var) in vars.iter().enumerate() {
+        out += if i == 0 { "\n" } else { ",\n" };
+        let mut fields = vec![
+            ("type", json_string("string")),
+            ("description", json_string(&trimmed_lines(var.help))),
+        ];
+        fields.extend(type_constraints(var.type_name));
+        match var.kind {
+            VarKind::DefaultValue(value) if !var.secret => {
+                fields.push(("default", json_string(value)))
+            }
+            _ => {}
+        }
+        out += &format!("    {}: {{\n", json_string(var.name));
+        let fields: Vec<String> = fields
+            .into_iter()
+            .map(|(key, value)| format!("      \"{key}\": {value}"))
+            .collect();
+        out += &fields.join(",\n");
+        out += "\n    }";
+    }
+    out += "\n  },\n  \"required\": [";
+    let required: Vec<String> = vars
+        .iter()
+        .filter(|var| var.kind == VarKind::Required)
+        .map(|var| json_string(var.name))
+        .collect();
+    out += &required.join(", ");
+    out += "]\n}\n";
+    out
+}
+
+/// The JSON Schema keywords which constrain a string to what the `FromStr` implementation
+/// of the named type accepts, for the types we know about.
+fn type_constraints(type_name: &str) -> Vec<(&'static str, String)> {
+    // Types may be written with or without a path, like `u16` or `std::primitive::u16`,
+    // and `Duration` may be given a unit, like `Duration<Millis>`.
+    let name = type_name.split('<').next().unwrap_or(type_name);
+    let name = name.rsplit("::").next().unwrap_or(name);
+    let pattern = |pattern: &str| vec![("pattern", json_string(pattern))];
+    match name {
+        "u8" | "u16" | "u32" | "u64" | "u128" | "usize" => pattern("^\\+?[0-9]+$"),
+        "i8" | "i16" | "i32" | "i64" | "i128" | "isize" => pattern("^[+-]?[0-9]+$"),
+        "NonZeroU8" | "NonZeroU16" | "NonZeroU32" | "NonZeroU64" | "NonZeroU128"
+        | "NonZeroUsize" => pattern("^\\+?0*[1-9][0-9]*$"),
+        "NonZeroI8" | "NonZeroI16" | "NonZeroI32" | "NonZeroI64" | "NonZeroI128"
+        | "NonZeroIsize" => pattern("^[+-]?0*[1-9][0-9]*$"),
+        "bool" => vec![("enum", "[\"true\", \"false\"]".to_owned())],
+        // Any value at all sets a `Flag`.
+        "Flag" => Vec::new(),
+        // An empty `Toggle` counts as unset.
+        "Toggle" => pattern(
+            "^(|[01]|[Tt][Rr][Uu][Ee]|[Ff][Aa][Ll][Ss][Ee]|[Yy][Ee][Ss]|[Nn][Oo]|[Oo][Nn]|[Oo][Ff][Ff])$",
+        ),
+        "char" => vec![("minLength", "1".to_owned()), ("maxLength", "1".to_owned())],
+        "ByteSize" => pattern("^\\s*[0-9]+\\s*(([kKmMgGtTpPeE][iI]?)?[bB]?)\\s*$"),
+        "Duration" => pattern("^\\s*([0-9]+|([0-9]+\\s*(ns|us|µs|ms|s|m|h|d)\\s*)+)\\s*$"),
+        _ => Vec::new(),
+    }
+}
+
+/// `text` with each of its lines trimmed, since indentation from the source code means nothing to readers.
+fn trimmed_lines(text: &str) -> String {
+    text.lines().map(str::trim).collect::<Vec<_>>().join("\n")
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::from('"');
+    for c in text.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\r' => out += "\\r",
+            '\t' => out += "\\t",
+            c if c.is_control() => out += &format!("\\u{:04x}", c as u32),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use regex::Regex;
+
+    use super::type_constraints;
+    use crate::{ByteSize, Duration};
+
+    /// Check that the schema pattern for `type_name` matches exactly those of `texts` which parse as a `T`.
+    fn assert_pattern_agrees<T: FromStr>(type_name: &str, texts: &[&str]) {
+        let (_, pattern) = type_constraints(type_name)
+            .into_iter()
+            .find(|&(key, _)| key == "pattern")
+            .unwrap();
+        // The pattern is written as a JSON string, which only escapes `\` here.
+        let pattern = pattern[1..pattern.len() - 1].replace("\\\\", "\\");
+        let regex = Regex::new(&pattern).unwrap();
+        for text in texts {
+            assert_eq!(
+                regex.is_match(text),
+                text.parse::<T>().is_ok(),
+                "{type_name} pattern {pattern:?} disagrees with the parser on {text:?}",
+            );
+        }
+    }
+
+    #[test]
+    fn byte_size_pattern() {
+        assert_pattern_agrees::<ByteSize>(
+            "ByteSize",
+            &[
+                "512", "7B", "7b", "64 kb", " 2G ", "1 Ki", "512MiB", "3eib", "", "B", "MiB",
+                "5iB", "5i", "5bB", "5KiBB", "5 x", "-5", "1.5G",
+            ],
+        );
+    }
+
+    #[test]
+    fn duration_pattern() {
+        assert_pattern_agrees::<Duration>(
+            "Duration",
+            &[
+                "30", "30 ", " 30", "1h30m", "1h 30m ", "5 ms", "5µs", "", "h", "5x", "5 m s",
+                "1.5s", "-5",
+            ],
+        );
+    }
+}
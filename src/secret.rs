@@ -0,0 +1,68 @@
+use std::fmt;
+use std::str::FromStr;
+
+/// What menv prints in place of the value of a secret var.
+pub(crate) const REDACTED: &str = "***";
+
+/// A value which is never printed, for env vars holding things like passwords and API keys.
+///
+/// Its [`Debug`](fmt::Debug) and [`Display`](fmt::Display) implementations print `***`,
+/// while it parses like `T` does. Declaring a var with a type of `Secret<T>`, or any type containing one,
+/// like `Option<Secret<T>>` or `Map<String, Secret<T>>`, also marks it `#[secret]`,
+/// so menv redacts its value from its own output too.
+/// ```
+/// use std::collections::HashMap;
+/// use menv::Secret;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     api_key, "MENV_DOC_SECRET_KEY", Secret<String>,
+///     "MENV_DOC_SECRET_KEY should be set to the key for the users API";
+///
+///     pins!, "MENV_DOC_SECRET_PINS", menv::Map<String, Secret<u32>>,
+///     "MENV_DOC_SECRET_PINS should be set to the PIN of each admin, like `alice=1234`";
+/// }
+///
+/// let key = api_key_from(&HashMap::from([("MENV_DOC_SECRET_KEY", "hunter2")]));
+/// assert_eq!(key.expose(), "hunter2");
+/// assert_eq!(format!("{key} {key:?}"), "*** ***");
+///
+/// let e = pins_from(&HashMap::from([("MENV_DOC_SECRET_PINS", "alice=hunter2")])).unwrap_err();
+/// assert!(!format!("{e} {e:?}").contains("hunter2"));
+/// ```
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Secret<T>(T);
+impl<T> Secret<T> {
+    pub fn new(value: T) -> Self {
+        Self(value)
+    }
+    /// A reference to the value. Take care not to print it.
+    pub fn expose(&self) -> &T {
+        &self.0
+    }
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+impl<T> From<T> for Secret<T> {
+    fn from(value: T) -> Self {
+        Self(value)
+    }
+}
+impl<T: FromStr> FromStr for Secret<T> {
+    type Err = T::Err;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+impl<T> fmt::Debug for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+impl<T> fmt::Display for Secret<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
@@ -0,0 +1,160 @@
+use std::borrow::Borrow;
+use std::collections::{HashMap, HashSet};
+use std::env::{self, VarError};
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+
+use crate::secret::REDACTED;
+
+/// Somewhere env vars can be read from.
+///
+/// Every function generated by [`require_envs`](crate::require_envs) which reads env vars
+/// has a variant with `_from` appended to its name, which reads them from a given `Source`
+/// instead of from the process environment.
+/// This is useful in tests, which can't safely modify the environment of the process they run in.
+///
+/// Besides [`ProcessEnv`], this is implemented for `HashMap`s from names to values:
+/// ```
+/// use std::collections::HashMap;
+/// use menv::Source;
+///
+/// let vars = HashMap::from([("FERRISCRAFT_USERS_PORT", "25565")]);
+/// assert_eq!(vars.var("FERRISCRAFT_USERS_PORT").unwrap(), "25565");
+/// assert!(vars.var_os("FERRISCRAFT_USERS_DB").is_none());
+/// ```
+pub trait Source {
+    /// Look up the value of an env var, like [`std::env::var_os`].
+    fn var_os(&self, name: &str) -> Option<OsString>;
+
+    /// Look up the value of an env var as a `String`, like [`std::env::var`].
+    fn var(&self, name: &str) -> Result<String, VarError> {
+        match self.var_os(name) {
+            Some(value) => value.into_string().map_err(VarError::NotUnicode),
+            None => Err(VarError::NotPresent),
+        }
+    }
+
+    /// Combine this source with another, which is only read from for vars this one doesn't have.
+    fn or<S: Source>(self, fallback: S) -> Or<Self, S>
+    where
+        Self: Sized,
+    {
+        Or {
+            first: self,
+            fallback,
+        }
+    }
+}
+
+/// A source which reads from one source, falling back to another, as returned by [`Source::or`].
+#[derive(Clone, Debug)]
+pub struct Or<A, B> {
+    first: A,
+    fallback: B,
+}
+impl<A: Source, B: Source> Source for Or<A, B> {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.first
+            .var_os(name)
+            .or_else(|| self.fallback.var_os(name))
+    }
+}
+
+/// The environment of the current process, as read by [`std::env`](mod@std::env).
+#[derive(Default, Copy, Clone, Debug)]
+pub struct ProcessEnv;
+impl Source for ProcessEnv {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        env::var_os(name)
+    }
+    fn var(&self, name: &str) -> Result<String, VarError> {
+        env::var(name)
+    }
+}
+
+impl<K, V, S> Source for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<OsStr>,
+    S: BuildHasher,
+{
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.get(name).map(|value| value.as_ref().to_owned())
+    }
+}
+
+impl<T: Source + ?Sized> Source for &T {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        (**self).var_os(name)
+    }
+    fn var(&self, name: &str) -> Result<String, VarError> {
+        (**self).var(name)
+    }
+}
+
+/// The values of a list of env vars, read once and never updated.
+///
+/// A `require_envs!` invocation given `snapshot = name` generates a function which takes a snapshot
+/// of all its vars the first time it's called, and makes the other generated functions read from it.
+///
+/// Its [`Debug`](fmt::Debug) implementation shows `***` in place of the values of vars
+/// marked `#[secret]`.
+#[derive(Clone, Default)]
+pub struct Snapshot {
+    vars: HashMap<String, OsString>,
+    secret: HashSet<String>,
+}
+impl Snapshot {
+    /// Read the given env vars from the process environment.
+    pub fn capture(names: &[&str]) -> Self {
+        Self::capture_from(&ProcessEnv, names)
+    }
+    /// Read the given env vars from `source`.
+    pub fn capture_from(source: &(impl Source + ?Sized), names: &[&str]) -> Self {
+        let vars = names
+            .iter()
+            .filter_map(|&name| Some((name.to_owned(), source.var_os(name)?)))
+            .collect();
+        Self {
+            vars,
+            secret: HashSet::new(),
+        }
+    }
+    /// Make the [`Debug`](fmt::Debug) output show `***` in place of the values of the given vars.
+    pub fn redacting(mut self, names: &[&str]) -> Self {
+        self.secret
+            .extend(names.iter().map(|&name| name.to_owned()));
+        self
+    }
+}
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut vars: Vec<(&String, OsString)> = self
+            .vars
+            .iter()
+            .map(|(name, value)| match self.secret.contains(name) {
+                true => (name, REDACTED.into()),
+                false => (name, value.clone()),
+            })
+            .collect();
+        vars.sort();
+        f.debug_struct("Snapshot")
+            .field("vars", &DebugMap(vars))
+            .finish()
+    }
+}
+
+struct DebugMap<'a>(Vec<(&'a String, OsString)>);
+impl fmt::Debug for DebugMap<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|(k, v)| (k, v)))
+            .finish()
+    }
+}
+impl Source for Snapshot {
+    fn var_os(&self, name: &str) -> Option<OsString> {
+        self.vars.get(name).cloned()
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+
+use crate::render::aligned_table;
+use crate::{EnvError, VarInfo, VarKind};
+
+/// The state of every env var in a [`require_envs`](crate::require_envs) invocation,
+/// as returned by the function generated by `status = name`.
+///
+/// Its [`Display`](fmt::Display) implementation prints a table with a row for each var.
+/// The values of vars are never printed.
+/// ```
+/// use std::collections::HashMap;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help, status = env_status);
+///
+///     server_port, "FERRISCRAFT_USERS_PORT", u16,
+///     "FERRISCRAFT_USERS_PORT should be set to the desired server port";
+///
+///     db_path, "FERRISCRAFT_USERS_DB", String,
+///     "FERRISCRAFT_USERS_DB should be set to the path to the users database";
+///
+///     plugin_dir?, "XLANG_PLUGIN_DIR", String,
+///     "XLANG_PLUGIN_DIR, if set, overrides the directory that lccc looks for xlang plugins";
+///
+///     view_distance = 12, "FERRISCRAFT_VIEW_DISTANCE", u8,
+///     "FERRISCRAFT_VIEW_DISTANCE, if set, overrides the view distance";
+/// }
+///
+/// let status = env_status_from(&HashMap::from([("FERRISCRAFT_USERS_PORT", "lots")]));
+/// assert!(!status.is_ok());
+/// assert_eq!(status.to_string(), "\
+/// VAR                        STATUS
+/// FERRISCRAFT_USERS_PORT     invalid: invalid digit found in string
+/// FERRISCRAFT_USERS_DB       missing
+/// XLANG_PLUGIN_DIR           unset
+/// FERRISCRAFT_VIEW_DISTANCE  unset, using default: 12
+/// ");
+/// ```
+#[derive(Debug)]
+pub struct Status {
+    vars: Vec<VarStatus>,
+}
+impl Status {
+    /// Whether every required var is set, and every var which is set is valid.
+    pub fn is_ok(&self) -> bool {
+        self.vars.iter().all(|var| var.state.is_ok())
+    }
+    pub fn iter(&self) -> std::slice::Iter<'_, VarStatus> {
+        self.vars.iter()
+    }
+}
+impl FromIterator<VarStatus> for Status {
+    fn from_iter<I: IntoIterator<Item = VarStatus>>(iter: I) -> Self {
+        Self {
+            vars: iter.into_iter().collect(),
+        }
+    }
+}
+impl IntoIterator for Status {
+    type Item = VarStatus;
+    type IntoIter = std::vec::IntoIter<VarStatus>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.vars.into_iter()
+    }
+}
+impl<'a> IntoIterator for &'a Status {
+    type Item = &'a VarStatus;
+    type IntoIter = std::slice::Iter<'a, VarStatus>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.vars.iter()
+    }
+}
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = self.vars.iter().map(|var| match var.state {
+            VarState::Defaulted => [
+                var.info.name.to_owned(),
+                format!("unset, using {}", var.info.describe()),
+            ],
+            _ => [var.info.name.to_owned(), var.state.to_string()],
+        });
+        f.write_str(&aligned_table(["VAR", "STATUS"], rows))
+    }
+}
+
+/// The state of a single env var.
+#[derive(Debug)]
+pub struct VarStatus {
+    pub info: VarInfo,
+    pub state: VarState,
+}
+
+/// Whether a var is set, and if it is, whether its value is valid.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum VarState {
+    /// The var is set to a valid value.
+    Set,
+    /// The var is set, but its value is not valid.
+    Invalid(EnvError),
+    /// The var is required, but is not set.
+    Missing,
+    /// The var is optional, and is not set.
+    Unset,
+    /// The var is not set, so its default value is used.
+    Defaulted,
+}
+impl VarState {
+    /// Whether this state is fine, meaning the var is neither missing nor invalid.
+    pub fn is_ok(&self) -> bool {
+        !matches!(self, Self::Invalid(_) | Self::Missing)
+    }
+}
+impl fmt::Display for VarState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Set => f.write_str("set"),
+            // The error's own message would repeat the name of the var.
+            Self::Invalid(EnvError::Invalid { source, .. }) => write!(f, "invalid: {source}"),
+            Self::Invalid(EnvError::NotUnicode { .. }) => f.write_str("invalid: not valid unicode"),
+            Self::Invalid(e) => write!(f, "invalid: {e}"),
+            Self::Missing => f.write_str("missing"),
+            Self::Unset => f.write_str("unset"),
+            Self::Defaulted => f.write_str("unset, using default"),
+        }
+    }
+}
+
+impl VarStatus {
+    pub(crate) fn new<T>(info: VarInfo, x: Result<Option<T>, EnvError>) -> Self {
+        let state = match x {
+            Ok(Some(_)) => VarState::Set,
+            Err(e) => VarState::Invalid(e),
+            Ok(None) => match info.kind {
+                VarKind::Required => VarState::Missing,
+                VarKind::Optional => VarState::Unset,
+                VarKind::Default
+                | VarKind::DefaultValue(_)
+                | VarKind::DefaultExpr(_)
+                | VarKind::List => VarState::Defaulted,
+            },
+        };
+        Self { info, state }
+    }
+}
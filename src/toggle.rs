@@ -0,0 +1,74 @@
+use std::error::Error;
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+
+/// Use this type instead of a [`bool`] if you want a var to accept the usual ways of writing
+/// one: `1`, `true`, `yes` and `on` are true, while `0`, `false`, `no` and `off` are false,
+/// in any case. Unlike a [`Flag`](crate::Flag), `DEBUG=0` turns it off.
+///
+/// A var which is set to an empty value is treated as though it were unset, by the `any_set` function too.
+/// Like [`Flag`](crate::Flag), this is best used with the `~` getter modifier.
+/// ```
+/// use std::collections::HashMap;
+///
+/// menv::require_envs! {
+///     (assert_env_vars, any_set, gen_help);
+///
+///     debug~, "MENV_DOC_TOGGLE_DEBUG", menv::Toggle,
+///     "MENV_DOC_TOGGLE_DEBUG, if set to a true value, enables debug logging";
+///
+///     verbose!, "MENV_DOC_TOGGLE_VERBOSE", menv::Toggle,
+///     "MENV_DOC_TOGGLE_VERBOSE should be set to whether to log every request";
+/// }
+///
+/// assert!(*debug_from(&HashMap::from([("MENV_DOC_TOGGLE_DEBUG", "Yes")])));
+/// assert!(!*debug_from(&HashMap::from([("MENV_DOC_TOGGLE_DEBUG", "0")])));
+/// assert!(!*debug_from(&HashMap::from([("MENV_DOC_TOGGLE_DEBUG", "")])));
+/// assert!(any_set_from(&HashMap::from([("MENV_DOC_TOGGLE_DEBUG", "0")])));
+/// assert!(!any_set_from(&HashMap::from([("MENV_DOC_TOGGLE_DEBUG", "")])));
+/// assert_eq!(
+///     verbose_from(&HashMap::from([("MENV_DOC_TOGGLE_VERBOSE", "sometimes")])).unwrap_err().to_string(),
+///     r#"MENV_DOC_TOGGLE_VERBOSE="sometimes" is not a valid menv::Toggle: expected one of 1, true, yes, on, 0, false, no or off"#,
+/// );
+/// assert!(matches!(
+///     verbose_from(&HashMap::from([("MENV_DOC_TOGGLE_VERBOSE", "")])),
+///     Err(menv::EnvError::Missing { .. }),
+/// ));
+/// ```
+#[derive(Default, Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Toggle {
+    pub val: bool,
+}
+impl FromStr for Toggle {
+    type Err = ToggleError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let val = match s.to_ascii_lowercase().as_str() {
+            "1" | "true" | "yes" | "on" => true,
+            "0" | "false" | "no" | "off" => false,
+            _ => return Err(ToggleError),
+        };
+        Ok(Self { val })
+    }
+}
+impl Deref for Toggle {
+    type Target = bool;
+    fn deref(&self) -> &Self::Target {
+        &self.val
+    }
+}
+impl DerefMut for Toggle {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.val
+    }
+}
+
+/// The error returned when a [`Toggle`] is set to something other than one of its accepted spellings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ToggleError;
+impl fmt::Display for ToggleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("expected one of 1, true, yes, on, 0, false, no or off")
+    }
+}
+impl Error for ToggleError {}